@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use substreams_solana_core::address::{Address, Allowlist};
+
+const PROGRAM_IDS: [&str; 4] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+];
+
+fn allowlist(c: &mut Criterion) {
+    let addresses: Vec<Vec<u8>> = (0..1_000u32)
+        .map(|i| match i % 10 {
+            0 => bs58::decode(PROGRAM_IDS[(i / 10) as usize % PROGRAM_IDS.len()])
+                .into_vec()
+                .unwrap(),
+            _ => i.to_le_bytes().repeat(8),
+        })
+        .collect();
+
+    c.bench_function("base58 string comparison", |b| {
+        b.iter(|| {
+            addresses
+                .iter()
+                .filter(|address| {
+                    let address = Address(address);
+                    PROGRAM_IDS.iter().any(|id| address == *id)
+                })
+                .count()
+        })
+    });
+
+    let allowlist = Allowlist::from_base58(PROGRAM_IDS).unwrap();
+    c.bench_function("allowlist contains", |b| {
+        b.iter(|| {
+            addresses
+                .iter()
+                .filter(|address| black_box(&allowlist).contains(address))
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, allowlist);
+criterion_main!(benches);
@@ -0,0 +1,113 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use substreams_solana_core::{
+    block_view::InstructionView, pb::sf::solana::r#type::v1 as pb, Instruction,
+};
+
+/// Builds a block of `transactions` transactions each having `compiled` compiled
+/// instructions invoking `inner` inner instructions each.
+fn block(transactions: usize, compiled: usize, inner: usize) -> pb::Block {
+    let instruction = |program_id_index: u32| pb::CompiledInstruction {
+        program_id_index,
+        accounts: vec![0, 1, 2],
+        data: vec![3; 16],
+    };
+
+    let trx = pb::ConfirmedTransaction {
+        transaction: Some(pb::Transaction {
+            signatures: vec![vec![1; 64]],
+            message: Some(pb::Message {
+                account_keys: (0..8).map(|i| vec![i; 32]).collect(),
+                instructions: (0..compiled)
+                    .map(|i| instruction(3 + (i % 5) as u32))
+                    .collect(),
+                ..Default::default()
+            }),
+        }),
+        meta: Some(pb::TransactionStatusMeta {
+            inner_instructions: (0..compiled)
+                .map(|index| pb::InnerInstructions {
+                    index: index as u32,
+                    instructions: (0..inner)
+                        .map(|i| pb::InnerInstruction {
+                            program_id_index: 3 + (i % 5) as u32,
+                            accounts: vec![1, 2],
+                            data: vec![4; 8],
+                            stack_height: Some(2),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            ..Default::default()
+        }),
+    };
+
+    pb::Block {
+        transactions: vec![trx; transactions],
+        ..Default::default()
+    }
+}
+
+/// Measures an instruction through the [InstructionView] accessors, resolving its program
+/// id and reading its data and account indices.
+fn measure(view: InstructionView) -> usize {
+    black_box(view.program_id()).0.len() + view.data().len() + view.account_indices().len()
+}
+
+/// Measures the same fields as [measure] by traversing the raw protobuf types of the block
+/// by hand, the lower bound of what the instruction iterators can achieve.
+fn measure_raw(block: &pb::Block) -> usize {
+    let mut total = 0;
+    for trx in block.transactions() {
+        let message = trx.transaction.as_ref().unwrap().message.as_ref().unwrap();
+        let meta = trx.meta.as_ref().unwrap();
+
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            total += measure_instruction(&message.account_keys, instruction);
+
+            let inner = meta
+                .inner_instructions
+                .iter()
+                .find(|inner| inner.index == index as u32);
+            for instruction in inner.iter().flat_map(|inner| inner.instructions.iter()) {
+                total += measure_instruction(&message.account_keys, instruction);
+            }
+        }
+    }
+
+    total
+}
+
+fn measure_instruction(account_keys: &[Vec<u8>], instruction: &impl Instruction) -> usize {
+    black_box(&account_keys[instruction.program_id_index() as usize]).len()
+        + instruction.data().len()
+        + instruction.accounts().len()
+}
+
+fn walk_instructions(c: &mut Criterion) {
+    let block = block(500, 4, 6);
+
+    let mut group = c.benchmark_group("instruction views");
+    group.bench_function("Block::walk_instructions", |b| {
+        b.iter(|| {
+            black_box(&block)
+                .walk_instructions()
+                .map(measure)
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("Block::compiled_instructions", |b| {
+        b.iter(|| {
+            black_box(&block)
+                .compiled_instructions()
+                .map(|view| view.inner_instructions().map(measure).sum::<usize>() + measure(view))
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("raw protobuf traversal", |b| {
+        b.iter(|| measure_raw(black_box(&block)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, walk_instructions);
+criterion_main!(benches);
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::base58;
 
 /// A wrapper around a byte array that represents a Solana address.
@@ -6,9 +8,258 @@ pub struct Address<'a>(pub &'a Vec<u8>);
 
 impl Address<'_> {
     /// Returns the address as a base58 encoded string.
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
         base58::encode(self.0)
     }
+
+    /// Returns an owned copy of the address, which can outlive the block it comes from.
+    pub fn to_owned(&self) -> AddressOwned {
+        AddressOwned(self.0.clone())
+    }
+}
+
+/// An owned version of [Address], useful to keep an address around after the block
+/// it comes from has been dropped, or as a key in collections.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct AddressOwned(pub Vec<u8>);
+
+/// An alias of [AddressOwned], following the `Path`/`PathBuf` naming of the standard
+/// library for the owned counterpart of [Address].
+pub type AddressBuf = AddressOwned;
+
+impl AddressOwned {
+    /// Returns a borrowed [Address] over this owned address.
+    pub fn as_address(&self) -> Address<'_> {
+        Address(&self.0)
+    }
+
+    /// Returns the address as a base58 encoded string.
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        base58::encode(&self.0)
+    }
+}
+
+impl From<Address<'_>> for AddressOwned {
+    fn from(address: Address<'_>) -> Self {
+        AddressOwned(address.0.clone())
+    }
+}
+
+impl From<&Address<'_>> for AddressOwned {
+    fn from(address: &Address<'_>) -> Self {
+        AddressOwned(address.0.clone())
+    }
+}
+
+impl From<Vec<u8>> for AddressOwned {
+    fn from(bytes: Vec<u8>) -> Self {
+        AddressOwned(bytes)
+    }
+}
+
+impl std::fmt::Debug for AddressOwned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&base58::encode(&self.0))
+    }
+}
+
+impl std::fmt::Display for AddressOwned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&base58::encode(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for AddressOwned {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl PartialEq<Address<'_>> for AddressOwned {
+    fn eq(&self, other: &Address<'_>) -> bool {
+        self.0 == *other.0
+    }
+}
+
+impl PartialEq<Vec<u8>> for AddressOwned {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for AddressOwned {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.0.as_slice() == other
+    }
+}
+
+/// A 32 bytes public key stored inline, typically used for program id constants with
+/// `substreams_solana::pubkey!`. It displays as base58 and compares with [Address]:
+///
+/// ```
+/// # use substreams_solana_core::address::Pubkey32;
+/// const TOKEN_PROGRAM_ID: Pubkey32 = Pubkey32::new([6; 32]);
+///
+/// assert_eq!("QWmroo4YnnMqYW3cnxWkFdaTxGD3P7vMSzwMHGbUzwF", TOKEN_PROGRAM_ID.to_string());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Pubkey32(pub [u8; 32]);
+
+impl Pubkey32 {
+    /// Creates a new public key from its bytes.
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Pubkey32(bytes)
+    }
+
+    /// Returns the bytes of the public key.
+    pub const fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl std::fmt::Debug for Pubkey32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&base58::encode(self.0))
+    }
+}
+
+impl std::fmt::Display for Pubkey32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&base58::encode(self.0))
+    }
+}
+
+impl AsRef<[u8]> for Pubkey32 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for Pubkey32 {
+    fn from(bytes: [u8; 32]) -> Self {
+        Pubkey32(bytes)
+    }
+}
+
+impl PartialEq<Address<'_>> for Pubkey32 {
+    fn eq(&self, other: &Address<'_>) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl PartialEq<&Address<'_>> for Pubkey32 {
+    fn eq(&self, other: &&Address<'_>) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl PartialEq<AddressOwned> for Pubkey32 {
+    fn eq(&self, other: &AddressOwned) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+#[cfg(feature = "solana-program")]
+impl Address<'_> {
+    /// Converts the address to a [solana_program::pubkey::Pubkey].
+    ///
+    /// The method panics if the address is not exactly 32 bytes long, which never happens
+    /// for the accounts of a valid Solana transaction.
+    pub fn to_pubkey(&self) -> solana_program::pubkey::Pubkey {
+        solana_program::pubkey::Pubkey::try_from(self.0.as_slice())
+            .unwrap_or_else(|_| panic!("address {} is not 32 bytes long", self))
+    }
+}
+
+/// A resolved account of a transaction along with its signer and writable privileges,
+/// see [crate::pb::sf::solana::r#type::v1::ConfirmedTransaction::account_metas].
+#[derive(Debug)]
+pub struct AccountMeta<'a> {
+    pub address: Address<'a>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// The segment of the transaction's resolved accounts an account comes from, see
+/// [crate::pb::sf::solana::r#type::v1::ConfirmedTransaction::account_source].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountSource {
+    /// One of the message's static account keys.
+    Static,
+    /// One of the meta's loaded writable addresses, loaded through an address lookup table.
+    LoadedWritable,
+    /// One of the meta's loaded readonly addresses, loaded through an address lookup table.
+    LoadedReadonly,
+}
+
+/// A set of addresses, typically program ids, decoded once from their base58 form so that
+/// matching an address against it is a byte comparison rather than decoding the base58
+/// strings on each comparison like `address == "<base58>"` does. Prefer it when matching
+/// many instructions against a fixed set of programs.
+///
+/// ```
+/// # use substreams_solana_core::{address::Allowlist, base58};
+/// let allowlist = Allowlist::from_base58([
+///     "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+///     "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+/// ])
+/// .unwrap();
+///
+/// let token_program = base58::decode("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+/// assert!(allowlist.contains(&token_program));
+/// assert!(!allowlist.contains(&[0; 32]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Allowlist {
+    addresses: HashSet<Vec<u8>>,
+}
+
+impl Allowlist {
+    /// Creates an allowlist from raw addresses.
+    pub fn new<I, T>(addresses: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Vec<u8>>,
+    {
+        Allowlist {
+            addresses: addresses.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Creates an allowlist by decoding base58 encoded addresses. On failure, the offending
+    /// value is returned alongside the decoding error, like [base58::decode_list].
+    pub fn from_base58<I, T>(addresses: I) -> Result<Self, (String, bs58::decode::Error)>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let addresses = addresses
+            .into_iter()
+            .map(|address| {
+                let address = address.as_ref();
+                base58::decode(address).map_err(|e| (address.to_string(), e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Allowlist { addresses })
+    }
+
+    /// Returns `true` if `address` is in the allowlist.
+    pub fn contains(&self, address: &[u8]) -> bool {
+        self.addresses.contains(address)
+    }
+
+    /// Returns the number of addresses in the allowlist.
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Returns `true` if the allowlist has no address.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
 }
 
 impl<'a> std::fmt::Debug for Address<'a> {
@@ -59,9 +310,138 @@ impl PartialEq<&Address<'_>> for Vec<u8> {
     }
 }
 
-impl<T: AsRef<[u8]>> PartialEq<T> for Address<'_> {
-    fn eq(&self, other: &T) -> bool {
-        self.0 == other.as_ref()
+impl PartialEq<Address<'_>> for Address<'_> {
+    fn eq(&self, other: &Address<'_>) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl Eq for Address<'_> {}
+
+/// Hashes the address' bytes, consistently with [PartialEq] and with [AddressOwned]'s hash.
+impl std::hash::Hash for Address<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl PartialEq<&Address<'_>> for Address<'_> {
+    fn eq(&self, other: &&Address<'_>) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl PartialEq<AddressOwned> for Address<'_> {
+    fn eq(&self, other: &AddressOwned) -> bool {
+        *self.0 == other.0
+    }
+}
+
+impl PartialEq<Vec<u8>> for Address<'_> {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&Vec<u8>> for Address<'_> {
+    fn eq(&self, other: &&Vec<u8>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<[u8]> for Address<'_> {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0.as_slice() == other
+    }
+}
+
+impl PartialEq<&[u8]> for Address<'_> {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.0.as_slice() == *other
+    }
+}
+
+impl PartialEq<Box<[u8]>> for Address<'_> {
+    fn eq(&self, other: &Box<[u8]>) -> bool {
+        self.0.as_slice() == other.as_ref()
+    }
+}
+
+impl PartialEq<std::borrow::Cow<'_, [u8]>> for Address<'_> {
+    fn eq(&self, other: &std::borrow::Cow<'_, [u8]>) -> bool {
+        self.0.as_slice() == other.as_ref()
+    }
+}
+
+#[cfg(feature = "solana-program")]
+impl PartialEq<solana_program::pubkey::Pubkey> for Address<'_> {
+    fn eq(&self, other: &solana_program::pubkey::Pubkey) -> bool {
+        self.0.as_slice() == other.as_ref()
+    }
+}
+
+#[cfg(feature = "solana-program")]
+impl PartialEq<solana_program::pubkey::Pubkey> for &Address<'_> {
+    fn eq(&self, other: &solana_program::pubkey::Pubkey) -> bool {
+        self.0.as_slice() == other.as_ref()
+    }
+}
+
+#[cfg(feature = "solana-program")]
+impl PartialEq<solana_program::pubkey::Pubkey> for AddressOwned {
+    fn eq(&self, other: &solana_program::pubkey::Pubkey) -> bool {
+        self.0.as_slice() == other.as_ref()
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Address<'_> {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.0.as_slice() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for Address<'_> {
+    fn eq(&self, other: &&[u8; N]) -> bool {
+        self.0.as_slice() == *other
+    }
+}
+
+impl PartialEq<Pubkey32> for Address<'_> {
+    fn eq(&self, other: &Pubkey32) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl PartialEq<Pubkey32> for &Address<'_> {
+    fn eq(&self, other: &Pubkey32) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl PartialEq<Pubkey32> for AddressOwned {
+    fn eq(&self, other: &Pubkey32) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+/// Compares against a base58 encoded address, `false` if the string is not valid base58.
+impl PartialEq<str> for Address<'_> {
+    fn eq(&self, other: &str) -> bool {
+        base58::decode(other).map_or(false, |bytes| *self.0 == bytes)
+    }
+}
+
+/// Compares against a base58 encoded address, `false` if the string is not valid base58.
+impl PartialEq<&str> for Address<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// Compares against a base58 encoded address, `false` if the string is not valid base58.
+impl PartialEq<String> for Address<'_> {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
     }
 }
 
@@ -91,7 +471,9 @@ impl<const N: usize> PartialEq<&Address<'_>> for [u8; N] {
 
 #[cfg(test)]
 mod tests {
-    use super::Address;
+    use std::collections::HashMap;
+
+    use super::{Address, AddressBuf, AddressOwned, Allowlist, Pubkey32};
 
     #[test]
     fn it_address_equality_works() {
@@ -128,5 +510,123 @@ mod tests {
         assert_eq!(&[1, 2, 3], address);
         assert_eq!(&fixed, address);
         assert_eq!([1u8, 2u8, 3u8], address);
+
+        let boxed: Box<[u8]> = vec![1, 2, 3].into_boxed_slice();
+        assert_eq!(address, boxed);
+        assert_eq!(address, std::borrow::Cow::Borrowed(&fixed[..]));
+        assert_ne!(address, std::borrow::Cow::<[u8]>::Owned(vec![1, 2]));
+    }
+
+    #[cfg(feature = "solana-program")]
+    #[test]
+    fn it_address_pubkey_equality_works() {
+        let pubkey = solana_program::pubkey::Pubkey::new_from_array([7; 32]);
+        let data = vec![7; 32];
+        let address = Address(&data);
+
+        assert_eq!(address, pubkey);
+        assert_eq!(&address, pubkey);
+        assert_eq!(AddressOwned(data.clone()), pubkey);
+        assert_ne!(Address(&vec![7; 31]), pubkey);
+    }
+
+    #[test]
+    fn it_converts_address_to_owned() {
+        struct Accumulator {
+            program_id: AddressBuf,
+        }
+
+        let accumulator = {
+            let data = vec![1, 2, 3];
+            let address = Address(&data);
+            let accumulator = Accumulator {
+                program_id: address.to_owned(),
+            };
+
+            assert_eq!(address, accumulator.program_id);
+            assert_eq!(accumulator.program_id, address);
+            assert_eq!(accumulator.program_id.as_address(), address);
+            accumulator
+        };
+
+        assert_eq!(accumulator.program_id, [1, 2, 3]);
+        assert_eq!("Ldp", accumulator.program_id.to_string());
+        assert_eq!("Ldp", format!("{:?}", accumulator.program_id));
+    }
+
+    #[test]
+    fn it_hashes_address_by_content() {
+        let first = vec![1, 2, 3];
+        let second = vec![1, 2, 3];
+        let other = vec![4, 5, 6];
+
+        let mut counts = HashMap::new();
+        *counts.entry(Address(&first)).or_insert(0u64) += 1;
+        *counts.entry(Address(&second)).or_insert(0u64) += 1;
+        *counts.entry(Address(&other)).or_insert(0u64) += 1;
+
+        assert_eq!(2, counts.len());
+        assert_eq!(Some(&2), counts.get(&Address(&first)));
+        assert_eq!(Some(&1), counts.get(&Address(&other)));
+    }
+
+    #[test]
+    fn it_address_owned_equality_works() {
+        let data: Vec<u8> = vec![1, 2, 3];
+        let address = Address(&data);
+        let owned: AddressOwned = address.into();
+
+        assert_eq!(owned, Address(&data));
+        assert_eq!(Address(&data), owned);
+        assert_eq!(owned, vec![1, 2, 3]);
+        assert_eq!(owned, [1u8, 2u8, 3u8]);
+        assert_eq!(owned.as_address(), Address(&data));
+        assert_eq!("Ldp", owned.to_string());
+        assert_eq!("Ldp", format!("{:?}", owned));
+    }
+
+    #[test]
+    fn it_address_base58_equality_works() {
+        let data: Vec<u8> = vec![1, 2, 3];
+        let address = Address(&data);
+
+        assert_eq!(address, "Ldp".to_string());
+        assert_eq!(address, "Ldp");
+        assert_ne!(address, "Ldq".to_string());
+        assert_ne!(address, "0OIl".to_string());
+        assert_ne!(address, String::new());
+    }
+
+    #[test]
+    fn it_pubkey32_equality_works() {
+        let data: Vec<u8> = vec![6; 32];
+        let address = Address(&data);
+        let pubkey = Pubkey32::new([6; 32]);
+
+        assert_eq!(pubkey, address);
+        assert_eq!(address, pubkey);
+        assert_eq!(&address, pubkey);
+        assert_eq!(pubkey, AddressOwned(data.clone()));
+        assert_eq!(AddressOwned(data.clone()), pubkey);
+        assert_ne!(Pubkey32::new([7; 32]), address);
+        assert_eq!(address.to_string(), pubkey.to_string());
+    }
+
+    #[test]
+    fn it_matches_allowlist() {
+        let allowlist = Allowlist::from_base58(["Ldp", "2MJu"]).unwrap();
+        assert_eq!(Allowlist::new([vec![1, 2, 3], vec![4, 5, 6]]), allowlist);
+        assert_eq!(2, allowlist.len());
+
+        let data: Vec<u8> = vec![4, 5, 6];
+        assert!(allowlist.contains(&[1, 2, 3]));
+        assert!(allowlist.contains(Address(&data).as_ref()));
+        assert!(!allowlist.contains(&[1, 2]));
+
+        let (value, _) =
+            Allowlist::from_base58(vec!["Ldp".to_string(), "0OIl".to_string()]).unwrap_err();
+        assert_eq!("0OIl", value);
+
+        assert!(Allowlist::default().is_empty());
     }
 }
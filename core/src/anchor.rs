@@ -0,0 +1,68 @@
+use crate::block_view::InstructionView;
+
+/// Discriminator of the self-CPI instruction emitted by Anchor's `emit_cpi!` macro, the first
+/// 8 bytes of `sha256("anchor:event")` (`EVENT_IX_TAG` in Anchor, encoded in little-endian).
+pub const EVENT_CPI_DISCRIMINATOR: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+impl InstructionView<'_> {
+    /// Returns the serialized event payload if the instruction is an Anchor event emitted
+    /// through `emit_cpi!`, [None] otherwise.
+    ///
+    /// With `emit_cpi!`, the program invokes itself with an instruction whose data is laid
+    /// out as:
+    ///
+    /// - 8 bytes: the event CPI tag, see [EVENT_CPI_DISCRIMINATOR]
+    /// - 8 bytes: the event discriminator, the first 8 bytes of `sha256("event:<EventName>")`
+    /// - rest: the Borsh serialized event
+    ///
+    /// The returned slice is the Borsh serialized event, e.g. the data after the event
+    /// discriminator. Use `&view.data()[8..16]` to get the event discriminator.
+    pub fn anchor_event_data(&self) -> Option<&[u8]> {
+        let data = self.data();
+        if data.len() < 16 || data[0..8] != EVENT_CPI_DISCRIMINATOR {
+            return None;
+        }
+
+        Some(&data[16..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EVENT_CPI_DISCRIMINATOR;
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    #[test]
+    fn it_extracts_anchor_event_data() {
+        let event_data = |data: Vec<u8>| {
+            let trx = pb::ConfirmedTransaction {
+                transaction: Some(pb::Transaction {
+                    signatures: vec![vec![1, 2, 3]],
+                    message: Some(pb::Message {
+                        account_keys: vec![vec![0xa0], vec![0xa1]],
+                        instructions: vec![pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data,
+                        }],
+                        ..Default::default()
+                    }),
+                }),
+                meta: Some(pb::TransactionStatusMeta::default()),
+            };
+
+            let view = trx.compiled_instructions().next().unwrap();
+            view.anchor_event_data().map(|data| data.to_vec())
+        };
+
+        let mut data = EVENT_CPI_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(Some(vec![]), event_data(data.clone()));
+
+        data.extend_from_slice(&[9, 10]);
+        assert_eq!(Some(vec![9, 10]), event_data(data));
+
+        assert_eq!(None, event_data(EVENT_CPI_DISCRIMINATOR.to_vec()));
+        assert_eq!(None, event_data(vec![1; 24]));
+    }
+}
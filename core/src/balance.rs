@@ -0,0 +1,181 @@
+//! Helpers to report the balance changes of a transaction.
+
+use std::collections::HashMap;
+
+use crate::{
+    address::AddressOwned,
+    base58,
+    pb::sf::solana::r#type::v1::{ConfirmedTransaction, TokenBalance},
+};
+
+/// The balance changes of a writable account of a transaction, see
+/// [ConfirmedTransaction::state_changes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateChange {
+    /// The account whose balances changed.
+    pub account: AddressOwned,
+    /// The change of the account's lamports balance.
+    pub lamport_delta: i128,
+    /// The change of the account's token balance, in raw amount, if it's a token account.
+    pub token_delta: Option<i128>,
+    /// The mint of the account, if it's a token account.
+    pub mint: Option<AddressOwned>,
+}
+
+impl ConfirmedTransaction {
+    /// Returns the balance changes of each writable account of the transaction (see
+    /// [Self::writable_accounts]), in resolved accounts order.
+    ///
+    /// The lamport delta is computed from the meta's pre and post balances. For token
+    /// accounts, e.g. accounts having a pre or post token balance, the token delta and mint
+    /// are also reported, an account present only in the pre (resp. post) token balances
+    /// being considered as having a post (resp. pre) amount of zero. Accounts whose balances
+    /// did not change are reported with zero deltas. Token balances with a missing or
+    /// unparseable amount are skipped, like [ConfirmedTransaction::token_deltas_by_owner]
+    /// does, the account being then reported without token delta nor mint.
+    ///
+    /// Returns an empty list if the transaction has no meta or no message.
+    pub fn state_changes(&self) -> Vec<StateChange> {
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return Vec::new(),
+        };
+        if self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .is_none()
+        {
+            return Vec::new();
+        }
+
+        // A [None] delta marks an account having a token balance whose amount is unparseable
+        let mut tokens: HashMap<u32, (Option<AddressOwned>, Option<i128>)> = HashMap::new();
+        for balance in meta.pre_token_balances.iter() {
+            let (mint, delta) = tokens
+                .entry(balance.account_index)
+                .or_insert((None, Some(0)));
+            *mint = mint.take().or_else(|| token_mint(balance));
+            *delta = delta.and_then(|delta| Some(delta - token_amount(balance)?));
+        }
+        for balance in meta.post_token_balances.iter() {
+            let (mint, delta) = tokens
+                .entry(balance.account_index)
+                .or_insert((None, Some(0)));
+            *mint = token_mint(balance).or_else(|| mint.take());
+            *delta = delta.and_then(|delta| Some(delta + token_amount(balance)?));
+        }
+
+        self.resolved_accounts()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_writable(*i as u8))
+            .map(|(i, account)| {
+                let pre = meta.pre_balances.get(i).copied().unwrap_or(0) as i128;
+                let post = meta.post_balances.get(i).copied().unwrap_or(0) as i128;
+                let token = tokens
+                    .remove(&(i as u32))
+                    .and_then(|(mint, delta)| delta.map(|delta| (mint, delta)));
+
+                StateChange {
+                    account: AddressOwned(account.clone()),
+                    lamport_delta: post - pre,
+                    token_delta: token.as_ref().map(|(_, delta)| *delta),
+                    mint: token.and_then(|(mint, _)| mint),
+                }
+            })
+            .collect()
+    }
+}
+
+fn token_mint(balance: &TokenBalance) -> Option<AddressOwned> {
+    base58::decode(&balance.mint).ok().map(AddressOwned)
+}
+
+fn token_amount(balance: &TokenBalance) -> Option<i128> {
+    balance
+        .ui_token_amount
+        .as_ref()
+        .and_then(|amount| amount.amount.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StateChange;
+    use crate::{address::AddressOwned, base58, pb::sf::solana::r#type::v1 as pb};
+
+    #[test]
+    fn it_reports_state_changes() {
+        let token_balance = |account_index: u32, amount: &str| pb::TokenBalance {
+            account_index,
+            mint: base58::encode([9; 32]),
+            ui_token_amount: Some(pb::UiTokenAmount {
+                amount: amount.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // Payer transfers 1 SOL to a1 and 250 tokens from its token account a2 to a3
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![vec![0xa0], vec![0xa1], vec![0xa2], vec![0xa3], vec![0xb0]],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                fee: 5_000,
+                pre_balances: vec![10_000_000_000, 0, 2_039_280, 2_039_280, 1],
+                post_balances: vec![8_999_995_000, 1_000_000_000, 2_039_280, 2_039_280, 1],
+                pre_token_balances: vec![token_balance(2, "1000")],
+                post_token_balances: vec![token_balance(2, "750"), token_balance(3, "250")],
+                ..Default::default()
+            }),
+        };
+
+        let change = |account: u8, lamport_delta: i128, token_delta: Option<i128>| StateChange {
+            account: AddressOwned(vec![account]),
+            lamport_delta,
+            token_delta,
+            mint: token_delta.map(|_| AddressOwned(vec![9; 32])),
+        };
+
+        assert_eq!(
+            vec![
+                change(0xa0, -1_000_005_000, None),
+                change(0xa1, 1_000_000_000, None),
+                change(0xa2, 0, Some(-250)),
+                change(0xa3, 0, Some(250)),
+            ],
+            trx.state_changes()
+        );
+
+        let mut malformed = trx.clone();
+        malformed.meta.as_mut().unwrap().pre_token_balances[0] = token_balance(2, "1,000");
+        malformed.meta.as_mut().unwrap().post_token_balances[1].ui_token_amount = None;
+        assert_eq!(
+            vec![
+                change(0xa0, -1_000_005_000, None),
+                change(0xa1, 1_000_000_000, None),
+                change(0xa2, 0, None),
+                change(0xa3, 0, None),
+            ],
+            malformed.state_changes()
+        );
+
+        let mut without_message = trx.clone();
+        without_message.transaction.as_mut().unwrap().message = None;
+        assert!(without_message.state_changes().is_empty());
+
+        let mut without_transaction = trx;
+        without_transaction.transaction = None;
+        assert!(without_transaction.state_changes().is_empty());
+    }
+}
@@ -1,5 +1,13 @@
-use crate::{address::Address, pb::sf::solana::r#type::v1 as pb, Instruction};
-use std::collections::HashMap;
+use crate::{
+    address::{AccountMeta, Address, AddressOwned},
+    base58,
+    compute_budget::COMPUTE_BUDGET_PROGRAM_ID,
+    error::{TransactionErrorKind, TransactionValidationError},
+    pb::sf::solana::r#type::v1 as pb,
+    programs::{ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, SYSTEM_PROGRAM_ID},
+    Instruction,
+};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 impl pb::Block {
     /// Iterates over successful transactions in given block.
@@ -22,26 +30,231 @@ impl pb::Block {
         })
     }
 
+    /// Iterates over all transactions of the block, successful and failed ones, along with
+    /// their [TransactionStatus], see [pb::ConfirmedTransaction::status].
+    pub fn transactions_tagged(
+        &self,
+    ) -> impl Iterator<Item = (TransactionStatus, &pb::ConfirmedTransaction)> {
+        self.transactions.iter().map(|trx| (trx.status(), trx))
+    }
+
+    /// Iterates over the ids (first signature) of all transactions of the block, failed
+    /// transactions included, as raw bytes. Handy to feed bloom filters or dedup structures
+    /// without allocating strings. Transactions without signature are skipped.
+    pub fn signatures(&self) -> impl Iterator<Item = &[u8]> {
+        self.transactions.iter().filter_map(|trx| {
+            trx.transaction
+                .as_ref()
+                .and_then(|trx| trx.signatures.first())
+                .map(|signature| signature.as_slice())
+        })
+    }
+
+    /// Returns the transaction of the block whose id (first signature) is `signature`,
+    /// failed transactions included, [None] if there is none.
+    ///
+    /// This is a linear scan over the block's transactions, prefer building your own index
+    /// when looking up many signatures in the same block.
+    pub fn transaction_by_signature(&self, signature: &[u8]) -> Option<&pb::ConfirmedTransaction> {
+        self.transactions.iter().find(|trx| {
+            trx.transaction
+                .as_ref()
+                .and_then(|trx| trx.signatures.first())
+                .map_or(false, |first| first.as_slice() == signature)
+        })
+    }
+
+    /// Returns `true` if the block holds a transaction whose id (first signature) is
+    /// `signature`, see [Self::transaction_by_signature].
+    pub fn contains_signature(&self, signature: &[u8]) -> bool {
+        self.transaction_by_signature(signature).is_some()
+    }
+
+    /// Iterates over the transactions of the block paid for by `payer`, failed transactions
+    /// included since they are charged fees too, see [pb::ConfirmedTransaction::fee_payer].
+    pub fn transactions_by_fee_payer<'a>(
+        &'a self,
+        payer: &'a [u8],
+    ) -> impl Iterator<Item = &'a pb::ConfirmedTransaction> {
+        self.transactions.iter().filter(move |trx| {
+            trx.fee_payer()
+                .map_or(false, |fee_payer| fee_payer.0.as_slice() == payer)
+        })
+    }
+
+    /// Iterates over the transactions of the block that failed with the given error kind,
+    /// see [pb::ConfirmedTransaction::classify_error].
+    pub fn transactions_failed_with(
+        &self,
+        kind: TransactionErrorKind,
+    ) -> impl Iterator<Item = &pb::ConfirmedTransaction> {
+        self.transactions
+            .iter()
+            .filter(move |trx| trx.classify_error() == Some(kind))
+    }
+
+    /// Returns the size in bytes of the block once encoded as protobuf, e.g. the size of the
+    /// block as received by the module. Handy to report ingestion throughput metrics.
+    ///
+    /// This is a shortcut for [prost::Message::encoded_len], the size is computed by walking
+    /// the whole block, so avoid calling it more than once per block.
+    pub fn byte_size(&self) -> usize {
+        prost::Message::encoded_len(self)
+    }
+
+    /// Returns the block height of the block if known. The block height is the number of
+    /// blocks beneath this block in the chain, it only increases when a block is actually
+    /// produced.
+    ///
+    /// Don't confuse it with the block's slot (see the `slot` field) which is the leader's
+    /// time window in which the block was produced. Since some slots are skipped (no block
+    /// produced), the slot is always greater than or equal to the block height and the gap
+    /// between the two grows over time.
+    ///
+    /// Returns [None] for old blocks where the block height was not recorded.
+    pub fn block_height(&self) -> Option<u64> {
+        self.block_height.as_ref().map(|height| height.block_height)
+    }
+
+    /// Returns the block's time as a UTC [time::OffsetDateTime], converted from the unix
+    /// timestamp in seconds of [pb::Block::block_time].
+    ///
+    /// Returns [None] if the block time was not recorded or is out of the range supported
+    /// by [time::OffsetDateTime].
+    #[cfg(feature = "time")]
+    pub fn block_datetime(&self) -> Option<time::OffsetDateTime> {
+        self.block_time
+            .as_ref()
+            .and_then(|time| time::OffsetDateTime::from_unix_timestamp(time.timestamp).ok())
+    }
+
+    /// Returns the distinct signers, fee payers included, of all successful transactions
+    /// of the block. Refer to [pb::ConfirmedTransaction::signers] for details.
+    pub fn signers(&self) -> HashSet<AddressOwned> {
+        self.transactions()
+            .flat_map(|trx| trx.signers())
+            .map(AddressOwned::from)
+            .collect()
+    }
+
+    /// Returns the block's rewards as `(pubkey, lamports, reward type)` tuples, in the same
+    /// order as [pb::Block::rewards]. The pubkey is decoded from its base58 representation,
+    /// rewards with an undecodable pubkey are skipped. A reward type unknown to this version
+    /// of the library is reported as [pb::RewardType::Unspecified].
+    pub fn reward_summary(&self) -> Vec<(AddressOwned, i64, pb::RewardType)> {
+        self.rewards
+            .iter()
+            .filter_map(|reward| {
+                let pubkey = base58::decode(&reward.pubkey).ok()?;
+                let reward_type = pb::RewardType::try_from(reward.reward_type)
+                    .unwrap_or(pb::RewardType::Unspecified);
+
+                Some((AddressOwned(pubkey), reward.lamports, reward_type))
+            })
+            .collect()
+    }
+
+    /// Iterates over all instructions, including inner instructions, of the block along
+    /// with their transaction and the log lines they emitted, see
+    /// [pb::ConfirmedTransaction::logs_by_instruction] for how logs are associated to
+    /// instructions.
+    ///
+    /// Like [Self::transactions], only successful transactions are covered.
+    pub fn instruction_logs(
+        &self,
+    ) -> impl Iterator<Item = (&pb::ConfirmedTransaction, InstructionView<'_>, Vec<&str>)> {
+        self.transactions().flat_map(|trx| {
+            let logs = trx.logs_by_instruction();
+
+            trx.walk_instructions()
+                .zip(logs.into_iter().chain(std::iter::repeat_with(Vec::new)))
+                .map(move |(view, logs)| (trx, view, logs))
+        })
+    }
+
     /// Iterates over compiled instructions of the block. Refer to [pb::ConfirmedTransaction::compiled_instructions]
     /// for details about the iteration.
-    pub fn compiled_instructions<'a>(&'a self) -> impl Iterator<Item = InstructionView<'a>> + 'a {
+    pub fn compiled_instructions(&self) -> impl Iterator<Item = InstructionView<'_>> + '_ {
         self.transactions()
-            .map(|trx| trx.compiled_instructions())
-            .flatten()
+            .flat_map(|trx| trx.compiled_instructions())
     }
 
     /// Iterates over all instructions, including inner instructions, of the block. Refer to
     /// [pb::ConfirmedTransaction::walk_instructions] for details about the iteration.
+    ///
+    /// The order is deterministic and guaranteed to stay so: transactions in block order,
+    /// then within a transaction each compiled instruction followed by its inner
+    /// instructions in invocation order. See [Self::walk_instructions_ordered] to get the
+    /// position of each instruction along.
     pub fn walk_instructions(&self) -> impl Iterator<Item = InstructionView> {
+        self.transactions().flat_map(|trx| trx.walk_instructions())
+    }
+
+    /// Returns the deepest stack height reached by the instructions of the block's
+    /// successful transactions, see [pb::ConfirmedTransaction::max_stack_height].
+    pub fn max_stack_height(&self) -> u32 {
         self.transactions()
-            .map(|trx| trx.walk_instructions())
-            .flatten()
+            .map(|trx| trx.max_stack_height())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Iterates over all instructions of the block like [Self::walk_instructions], in the
+    /// same order, pairing each instruction with its [InstructionPath]. The paths are
+    /// strictly increasing, making them suitable as stable keys for reproducible output.
+    pub fn walk_instructions_ordered(
+        &self,
+    ) -> impl Iterator<Item = (InstructionPath, InstructionView<'_>)> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, trx)| trx.meta.as_ref().map_or(false, |meta| meta.err.is_none()))
+            .flat_map(|(transaction_index, trx)| {
+                let mut compiled_index = 0;
+                let mut next_inner_index = 0;
+
+                trx.walk_instructions().map(move |view| {
+                    let inner_index = match view.compiled_index {
+                        Some(index) => {
+                            compiled_index = index;
+                            next_inner_index = 0;
+                            None
+                        }
+                        None => {
+                            next_inner_index += 1;
+                            Some(next_inner_index - 1)
+                        }
+                    };
+
+                    let path = InstructionPath {
+                        transaction_index,
+                        compiled_index,
+                        inner_index,
+                    };
+
+                    (path, view)
+                })
+            })
+    }
+
+    /// Iterates over all instructions of the block like [Self::walk_instructions_ordered],
+    /// yielding fully keyed rows of the block's slot, the transaction index, the
+    /// [InstructionPath] and the instruction, ready to be loaded in a data warehouse. The
+    /// transaction index is the same as [InstructionPath::transaction_index].
+    pub fn flat_instructions(
+        &self,
+    ) -> impl Iterator<Item = (u64, usize, InstructionPath, InstructionView<'_>)> {
+        let slot = self.slot;
+
+        self.walk_instructions_ordered()
+            .map(move |(path, view)| (slot, path.transaction_index, path, view))
     }
 }
 
 /// A view over an instruction when iterating over a transaction.
+#[derive(Clone)]
 pub struct InstructionView<'a> {
-    instruction: Box<dyn Instruction + 'a>,
+    instruction: InstructionRef<'a>,
     trx: &'a pb::ConfirmedTransaction,
     compiled_instruction: &'a pb::CompiledInstruction,
 
@@ -53,6 +266,43 @@ pub struct InstructionView<'a> {
 
 static EMPTY_INNER_INSTRUCTIONS: Vec<pb::InnerInstruction> = Vec::new();
 
+/// The protobuf instruction an [InstructionView] is over.
+#[derive(Clone, Copy)]
+enum InstructionRef<'a> {
+    Compiled(&'a pb::CompiledInstruction),
+    Inner(&'a pb::InnerInstruction),
+}
+
+impl Instruction for InstructionRef<'_> {
+    fn program_id_index(&self) -> u32 {
+        match self {
+            InstructionRef::Compiled(instruction) => instruction.program_id_index(),
+            InstructionRef::Inner(instruction) => instruction.program_id_index(),
+        }
+    }
+
+    fn accounts(&self) -> &Vec<u8> {
+        match self {
+            InstructionRef::Compiled(instruction) => instruction.accounts(),
+            InstructionRef::Inner(instruction) => instruction.accounts(),
+        }
+    }
+
+    fn data(&self) -> &Vec<u8> {
+        match self {
+            InstructionRef::Compiled(instruction) => instruction.data(),
+            InstructionRef::Inner(instruction) => instruction.data(),
+        }
+    }
+
+    fn stack_height(&self) -> Option<u32> {
+        match self {
+            InstructionRef::Compiled(instruction) => instruction.stack_height(),
+            InstructionRef::Inner(instruction) => instruction.stack_height(),
+        }
+    }
+}
+
 impl<'a> InstructionView<'a> {
     /// Returns the resolved program id defined by this instruction as an [Address]
     /// type which is a wrapper around a byte array and provide [Address::to_string]
@@ -62,12 +312,37 @@ impl<'a> InstructionView<'a> {
     /// # let instruction_view: substreams_solana_core::block_view::InstructionView = unimplemented!();
     /// let program_id = instruction_view.program_id().to_string();
     /// ```
-    pub fn program_id(&self) -> Address {
+    pub fn program_id(&self) -> Address<'a> {
         // &self.resolved_program_id
         self.trx
             .account_at(self.instruction.program_id_index() as u8)
     }
 
+    /// Returns `true` if the instruction's program id is one of `ids`, handy to dispatch over
+    /// several `b58!` program id constants. The program id is resolved once.
+    ///
+    /// ```no_run
+    /// # use substreams_solana_macro::b58;
+    /// # let instruction_view: substreams_solana_core::block_view::InstructionView = unimplemented!();
+    /// const TOKEN: [u8; 32] = b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    /// const TOKEN_2022: [u8; 32] = b58!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+    ///
+    /// if instruction_view.program_is_one_of(&[TOKEN, TOKEN_2022]) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn program_is_one_of(&self, ids: &[[u8; 32]]) -> bool {
+        let program_id = self.program_id();
+        ids.iter().any(|id| program_id == *id)
+    }
+
+    /// Same as [Self::program_is_one_of] but with program ids of any length, e.g. decoded at
+    /// runtime.
+    pub fn program_is_one_of_slices(&self, ids: &[&[u8]]) -> bool {
+        let program_id = self.program_id();
+        ids.iter().any(|id| program_id == *id)
+    }
+
     /// Returns the resolved accounts defined by this instruction. You can
     /// easily get the base58 encoded string of the addresses:
     ///
@@ -85,10 +360,130 @@ impl<'a> InstructionView<'a> {
             .collect()
     }
 
+    /// Iterates over the resolved accounts of this instruction, lazily resolving each
+    /// account index. Same as [Self::accounts] without allocating a [Vec], prefer it when
+    /// you only need to scan the accounts.
+    pub fn accounts_iter(&self) -> impl Iterator<Item = Address<'a>> + '_ {
+        self.instruction
+            .accounts()
+            .iter()
+            .map(|index| self.trx.account_at(*index))
+    }
+
+    /// Returns the raw account indices of this instruction, e.g. the indices of the accounts
+    /// in the transaction's resolved accounts (see [pb::ConfirmedTransaction::account_at]),
+    /// without resolving them. Useful to correlate with fields referring to an account index
+    /// like [pb::TokenBalance::account_index].
+    pub fn account_indices(&self) -> &[u8] {
+        self.instruction.accounts()
+    }
+
+    /// Returns the resolved accounts defined by this instruction along with their signer
+    /// and writable privileges in the transaction, in the same order as [Self::accounts].
+    ///
+    /// The metas follow the instruction's account ordering, not the transaction's, and an
+    /// account referenced multiple times by the instruction gets a meta at each of its
+    /// positions, which is what `solana_program` based decoders expect.
+    pub fn account_metas(&self) -> Vec<AccountMeta<'a>> {
+        self.instruction
+            .accounts()
+            .iter()
+            .map(|index| self.trx.account_meta_at(*index))
+            .collect()
+    }
+
+    /// Returns whether the instruction's account at the given position, e.g. the `position`-th
+    /// account of [Self::accounts], is writable by the transaction, see
+    /// [pb::ConfirmedTransaction::is_writable]. Returns [None] if the instruction has no
+    /// account at this position.
+    pub fn is_account_writable(&self, position: usize) -> Option<bool> {
+        self.instruction
+            .accounts()
+            .get(position)
+            .map(|index| self.trx.is_writable(*index))
+    }
+
+    /// Returns the [AccountMeta] of all resolved accounts of the transaction holding this
+    /// instruction, not only those of the instruction. Shortcut for
+    /// `view.confirmed_transaction().account_metas()`, see
+    /// [pb::ConfirmedTransaction::account_metas].
+    pub fn transaction_account_metas(&self) -> Vec<AccountMeta<'a>> {
+        self.trx.account_metas()
+    }
+
+    /// Returns the resolved accounts defined by this instruction as a set of byte slices,
+    /// for fast membership tests like checking the instruction's accounts against a
+    /// watch-list. Use [Self::accounts] if the accounts order matters.
+    ///
+    /// Each call allocates a new [HashSet] and resolves every account of the instruction,
+    /// so prefer keeping the returned set around when testing many candidates.
+    pub fn account_set(&self) -> HashSet<&'a [u8]> {
+        self.instruction
+            .accounts()
+            .iter()
+            .map(|index| self.trx.account_at(*index).0.as_slice())
+            .collect()
+    }
+
     pub fn data(&self) -> &Vec<u8> {
         self.instruction.data()
     }
 
+    /// Returns `true` if the instruction's data is exactly `expected`, handy to identify
+    /// fixed instructions without arguments against a constant, e.g. a `b58!` one. See
+    /// [Self::discriminator] to match a prefix of the data instead.
+    pub fn data_equals(&self, expected: &[u8]) -> bool {
+        self.instruction.data().as_slice() == expected
+    }
+
+    /// Returns `true` if both views hold equal instructions, that is with the same program
+    /// id index, account indices, data and stack height. This is value equality, not
+    /// identity: two identical instructions at different positions, or in different
+    /// transactions, are the same instruction. Note that indices are compared unresolved,
+    /// so instructions of different transactions may resolve to different accounts.
+    pub fn same_instruction(&self, other: &InstructionView<'_>) -> bool {
+        self.instruction.program_id_index() == other.instruction.program_id_index()
+            && self.instruction.accounts() == other.instruction.accounts()
+            && self.instruction.data() == other.instruction.data()
+            && self.instruction.stack_height() == other.instruction.stack_height()
+    }
+
+    /// Returns the first `len` bytes of the instruction's data, [None] if the data is shorter
+    /// than `len`. Handy to match the instruction against discriminators of any size:
+    ///
+    /// ```no_run
+    /// # let view: substreams_solana_core::block_view::InstructionView = unimplemented!();
+    /// const INITIALIZE: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+    ///
+    /// match view.discriminator(8) {
+    ///     Some(d) if d == INITIALIZE => { /* ... */ }
+    ///     _ => {}
+    /// }
+    ///
+    /// match view.discriminator(1) {
+    ///     Some([3]) => { /* ... */ }
+    ///     Some([7]) => { /* ... */ }
+    ///     _ => {}
+    /// }
+    /// ```
+    ///
+    /// See [Self::anchor_discriminator] for the 8 bytes Anchor selector as an array.
+    pub fn discriminator(&self, len: usize) -> Option<&[u8]> {
+        self.data().get(..len)
+    }
+
+    /// Returns the first 8 bytes of the instruction's data as an array, the Anchor-style
+    /// instruction selector (sighash), or [None] if the data is shorter than 8 bytes, see
+    /// [Instruction::anchor_discriminator].
+    pub fn anchor_discriminator(&self) -> Option<[u8; 8]> {
+        self.instruction.anchor_discriminator()
+    }
+
+    /// Returns the first byte of the instruction's data, see [Instruction::variant_tag].
+    pub fn variant_tag(&self) -> Option<u8> {
+        self.instruction.variant_tag()
+    }
+
     /// Returns the stack height of the instruction or zero if instruction does not have
     /// a stack height field which appeared in Solana v1.14.6 and activated around
     /// block 200M on Mainnet. So if you deal with block heights before that, you can
@@ -121,7 +516,7 @@ impl<'a> InstructionView<'a> {
                 .find(|i: &&pb::InnerInstructions| i.index == index as u32)
                 .and_then(|i| i.instructions.get(at))
                 .map(|instruction| InstructionView {
-                    instruction: Box::new(instruction),
+                    instruction: InstructionRef::Inner(instruction),
                     trx: self.trx,
                     compiled_instruction: self.compiled_instruction,
                     compiled_index: None,
@@ -129,10 +524,12 @@ impl<'a> InstructionView<'a> {
         }
     }
 
-    /// The inner instructions of the compiled instruction that holds this instruction.
-    /// It's the direct children of [Self::compiled_instruction]. This method will return
-    /// an empty iterator if the current instruction is not a compiled instruction, e.g.
-    /// [Self::is_root()] == false.
+    /// The inner instructions of the compiled instruction that holds this instruction, as
+    /// a flat list in invocation order. All the instructions invoked by
+    /// [Self::compiled_instruction] are returned, nested invocations included, use
+    /// [Self::children] to get only the instructions it directly invoked. This method will
+    /// return an empty iterator if the current instruction is not a compiled instruction,
+    /// e.g. [Self::is_root()] == false.
     ///
     /// If you are **not** in a compiled instruction and would still like to iterate over
     /// inner instructions, open an issue and we will consider adding a method to do that.
@@ -149,12 +546,145 @@ impl<'a> InstructionView<'a> {
         };
 
         inner.iter().map(move |inner_instruction| InstructionView {
-            instruction: Box::new(inner_instruction),
+            instruction: InstructionRef::Inner(inner_instruction),
             trx: self.trx,
             compiled_instruction: self.compiled_instruction,
             compiled_index: None,
         })
     }
+
+    /// Returns the instruction that invoked this one, [None] for compiled instructions. The
+    /// parent is reconstructed from the inner instructions' stack height, it's the closest
+    /// previous inner instruction of the same compiled instruction with a lower stack
+    /// height, or the compiled instruction itself if there is none.
+    pub fn parent(&self) -> Option<InstructionView<'a>> {
+        self.ancestors().next()
+    }
+
+    /// Iterates over the instructions in the CPI chain that led to this one, from its
+    /// direct parent, see [Self::parent], up to its compiled instruction. The iterator is
+    /// empty for compiled instructions.
+    pub fn ancestors(&self) -> impl Iterator<Item = InstructionView<'a>> {
+        let (family, position) = self.invocation_family();
+
+        let mut ancestors = Vec::new();
+        let mut parent = family[position].1;
+        while let Some(index) = parent {
+            ancestors.push(family[index].0.clone());
+            parent = family[index].1;
+        }
+
+        ancestors.into_iter()
+    }
+
+    /// Returns the instructions directly invoked by this one, in invocation order. This is
+    /// the reverse of [Self::parent], nested invocations are not returned, see
+    /// [Self::inner_instructions] for those.
+    pub fn children(&self) -> Vec<InstructionView<'a>> {
+        let (family, position) = self.invocation_family();
+
+        family
+            .into_iter()
+            .filter(|(_, parent)| *parent == Some(position))
+            .map(|(view, _)| view)
+            .collect()
+    }
+
+    /// Reconstructs the CPI nesting of the compiled instruction holding this instruction
+    /// with [InstructionAncestors], like [pb::ConfirmedTransaction::call_graph] does. Returns
+    /// the compiled instruction followed by its inner instructions, each along with the
+    /// position of its parent, and the position of this instruction.
+    fn invocation_family(&self) -> (Vec<(InstructionView<'a>, Option<usize>)>, usize) {
+        let (group, position) = match self.instruction {
+            InstructionRef::Compiled(_) => {
+                let group = self.compiled_index.and_then(|index| {
+                    self.trx
+                        .meta
+                        .as_ref()?
+                        .inner_instructions
+                        .iter()
+                        .find(|i| i.index == index as u32)
+                });
+
+                (group, 0)
+            }
+            InstructionRef::Inner(_) => match self.inner_position() {
+                Some((group, position)) => (Some(group), position + 1),
+                None => return (vec![(self.clone(), None)], 0),
+            },
+        };
+
+        let compiled = InstructionView {
+            instruction: InstructionRef::Compiled(self.compiled_instruction),
+            trx: self.trx,
+            compiled_instruction: self.compiled_instruction,
+            compiled_index: group
+                .map(|group| group.index as usize)
+                .or(self.compiled_index),
+        };
+        let inner = group
+            .into_iter()
+            .flat_map(|group| group.instructions.iter())
+            .map(|instruction| self.inner_view(instruction));
+
+        let mut family: Vec<(InstructionView<'a>, Option<usize>)> = Vec::new();
+        let mut ancestors = InstructionAncestors::new();
+        for view in std::iter::once(compiled).chain(inner) {
+            let parent = ancestors.unwind(&view).last().copied();
+            ancestors.push(&view, family.len());
+            family.push((view, parent));
+        }
+
+        (family, position)
+    }
+
+    /// Finds the inner instructions group holding this inner instruction along with its
+    /// position within the group, [None] for compiled instructions.
+    fn inner_position(&self) -> Option<(&'a pb::InnerInstructions, usize)> {
+        let inner = self.as_inner()?;
+
+        self.trx
+            .meta
+            .as_ref()?
+            .inner_instructions
+            .iter()
+            .find_map(|group| {
+                group
+                    .instructions
+                    .iter()
+                    .position(|instruction| std::ptr::eq(instruction, inner))
+                    .map(|position| (group, position))
+            })
+    }
+
+    fn inner_view(&self, instruction: &'a pb::InnerInstruction) -> InstructionView<'a> {
+        InstructionView {
+            instruction: InstructionRef::Inner(instruction),
+            trx: self.trx,
+            compiled_instruction: self.compiled_instruction,
+            compiled_index: None,
+        }
+    }
+
+    /// Returns the underlying protobuf [pb::CompiledInstruction] if this view is over a
+    /// compiled instruction, [None] if it's over an inner instruction. Useful to bridge
+    /// with code written against the raw protobuf types.
+    pub fn as_compiled(&self) -> Option<&'a pb::CompiledInstruction> {
+        match self.instruction {
+            InstructionRef::Compiled(instruction) => Some(instruction),
+            InstructionRef::Inner(_) => None,
+        }
+    }
+
+    /// Returns the underlying protobuf [pb::InnerInstruction] if this view is over an inner
+    /// instruction, [None] if it's over a compiled instruction.
+    pub fn as_inner(&self) -> Option<&'a pb::InnerInstruction> {
+        match self.instruction {
+            InstructionRef::Compiled(_) => None,
+            InstructionRef::Inner(instruction) => Some(instruction),
+        }
+    }
+
     /// Returns true if the instruction your are iterating over is a compiled instruction,
     /// e.g. a root instruction of a transaction or false if the view represents an
     /// inner instruction.
@@ -170,18 +700,59 @@ impl<'a> InstructionView<'a> {
     /// compiled instruction that holds the inner instructions.
     pub fn compiled_instruction(&self) -> InstructionView<'a> {
         InstructionView {
-            instruction: Box::new(self.compiled_instruction),
+            instruction: InstructionRef::Compiled(self.compiled_instruction),
             trx: self.trx,
             compiled_instruction: self.compiled_instruction,
             compiled_index: self.compiled_index,
         }
     }
 
+    /// Converts the instruction to a [solana_program::instruction::Instruction] with the
+    /// resolved program id, the account metas (see [Self::account_metas]) and the data, so it
+    /// can be fed to `solana_program` based decoders.
+    ///
+    /// The program id and all accounts must be 32 bytes long, which is always the case for a
+    /// valid Solana transaction, the method panics otherwise.
+    #[cfg(feature = "solana-program")]
+    pub fn to_solana_instruction(&self) -> solana_program::instruction::Instruction {
+        solana_program::instruction::Instruction {
+            program_id: self.program_id().to_pubkey(),
+            accounts: self
+                .account_metas()
+                .iter()
+                .map(|meta| solana_program::instruction::AccountMeta {
+                    pubkey: meta.address.to_pubkey(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: self.data().clone(),
+        }
+    }
+
+    /// The data of the compiled instruction within which this instruction was originally
+    /// found, same as `self.compiled_instruction().data()` but without building a new view.
+    /// This is useful to read the top-level discriminator while processing an inner
+    /// instruction, whatever its depth.
+    pub fn root_data(&self) -> &'a [u8] {
+        &self.compiled_instruction.data
+    }
+
     /// The transactions's message that holds this instruction.
+    ///
+    /// The method panics if the transaction has no message, which can't happen for views
+    /// obtained by iterating over a transaction's instructions since instructions are
+    /// read from the message. Use [Self::maybe_message] to be defensive.
     pub fn message(&self) -> &'a pb::Message {
         self.transaction().message.as_ref().unwrap()
     }
 
+    /// The transactions's message that holds this instruction, [None] if the transaction
+    /// or its message is missing. See [Self::message] for details.
+    pub fn maybe_message(&self) -> Option<&'a pb::Message> {
+        self.trx.transaction.as_ref()?.message.as_ref()
+    }
+
     /// The transactions's meta that holds this instruction
     pub fn meta(&self) -> &'a pb::TransactionStatusMeta {
         self.trx.meta.as_ref().unwrap()
@@ -216,14 +787,10 @@ impl pb::ConfirmedTransaction {
     ///   }
     /// }
     /// ```
-    pub fn compiled_instructions<'a>(&'a self) -> impl Iterator<Item = InstructionView<'a>> + 'a {
-        let mut inner_instructions_by_parent = HashMap::new();
-        if let Some(meta) = self.meta.as_ref() {
-            for inner_instructions in meta.inner_instructions.iter() {
-                inner_instructions_by_parent.insert(inner_instructions.index, inner_instructions);
-            }
-        }
-
+    ///
+    /// If the transaction or its message is missing, which only happens with malformed or partial
+    /// data, the iterator is empty.
+    pub fn compiled_instructions(&self) -> impl Iterator<Item = InstructionView<'_>> + '_ {
         self.transaction
             .iter()
             .flat_map(|trx| {
@@ -232,13 +799,30 @@ impl pb::ConfirmedTransaction {
                     .flat_map(|m| m.instructions.iter().enumerate())
             })
             .map(move |(i, inst)| InstructionView {
-                instruction: Box::new(inst),
+                instruction: InstructionRef::Compiled(inst),
                 trx: self,
                 compiled_instruction: inst,
                 compiled_index: Some(i),
             })
     }
 
+    /// Iterates over the compiled instructions of the transaction that invoked at least one
+    /// other instruction through CPI, e.g. those having inner instructions. Refer to
+    /// [Self::compiled_instructions] for details about the iteration.
+    pub fn compiled_instructions_with_cpis(
+        &self,
+    ) -> impl Iterator<Item = InstructionView<'_>> + '_ {
+        self.compiled_instructions().filter(move |view| {
+            let compiled_index = view.compiled_index.unwrap_or_default() as u32;
+
+            self.meta.as_ref().map_or(false, |meta| {
+                meta.inner_instructions
+                    .iter()
+                    .any(|inner| inner.index == compiled_index && !inner.instructions.is_empty())
+            })
+        })
+    }
+
     /// Iterates over all instructions, including inner instructions, of the transaction. The iteration
     /// starts with the first compiled instruction and then goes through all its inner instructions, if any.
     /// Then it moves to the next compiled instruction and so on recursively.
@@ -246,111 +830,839 @@ impl pb::ConfirmedTransaction {
     /// You receive a [InstructionView] for each instruction visited in the transaction. The [InstructionView]
     /// provides convenient access to the resolved [InstructionView::program_id] and [InstructionView::accounts]
     /// instead of the raw program id index & account indices.
-    pub fn walk_instructions<'a>(&'a self) -> impl Iterator<Item = InstructionView<'a>> + 'a {
-        let trx = self.transaction.as_ref().unwrap();
+    ///
+    /// If the transaction or its message is missing, which only happens with malformed or partial
+    /// data, the iterator is empty. Likewise, inner instructions whose compiled instruction index
+    /// is out of range are skipped, see [Self::validate] to detect them.
+    pub fn walk_instructions(&self) -> impl Iterator<Item = InstructionView<'_>> + '_ {
+        self.walk_instructions_from(0)
+    }
+
+    /// Checks the consistency of the transaction's instructions, returning the first
+    /// inconsistency found. Only corrupted data fails the validation, for now inner
+    /// instructions whose compiled instruction index is out of range, which
+    /// [Self::walk_instructions] skips.
+    pub fn validate(&self) -> Result<(), TransactionValidationError> {
+        let compiled_instruction_count = self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .map_or(0, |message| message.instructions.len());
+
+        let out_of_range = self.meta.as_ref().and_then(|meta| {
+            meta.inner_instructions
+                .iter()
+                .find(|inner| inner.index as usize >= compiled_instruction_count)
+        });
+
+        match out_of_range {
+            Some(inner) => Err(
+                TransactionValidationError::InnerInstructionsIndexOutOfRange {
+                    index: inner.index,
+                    compiled_instruction_count,
+                },
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Iterates over the compiled instruction at index `compiled_index` and all its inner
+    /// instructions, in the same depth-first order as [Self::walk_instructions]. This is
+    /// useful to process the full subtree of a single top-level instruction in isolation.
+    ///
+    /// The iterator is empty if there is no compiled instruction at `compiled_index`.
+    pub fn walk_subtree(
+        &self,
+        compiled_index: usize,
+    ) -> impl Iterator<Item = InstructionView<'_>> + '_ {
+        self.walk_instructions_from(compiled_index)
+            .take_while(move |view| view.compiled_index.map_or(true, |i| i == compiled_index))
+    }
+
+    /// Same as [Self::walk_instructions] but skips instructions whose stack height (see
+    /// [InstructionView::stack_height]) is above `max_depth`, compiled instructions being
+    /// at depth 0. A `max_depth` of 0 thus only yields compiled instructions.
+    ///
+    /// This is a defensive guard against pathological inner instruction structures, skipped
+    /// instructions are logged at debug level when the `tracing` feature is enabled.
+    pub fn walk_instructions_bounded(
+        &self,
+        max_depth: u32,
+    ) -> impl Iterator<Item = InstructionView<'_>> + '_ {
+        self.walk_instructions().filter(move |view| {
+            let stack_height = view.stack_height();
+            if stack_height <= max_depth {
+                return true;
+            }
+
+            #[cfg(feature = "tracing")]
+            ::tracing::debug!(
+                program_id = %view.program_id(),
+                stack_height,
+                max_depth,
+                "skipping instruction above maximum walk depth"
+            );
+
+            false
+        })
+    }
+
+    /// Iterates over the sliding windows of `size` consecutive instructions of the flat
+    /// depth-first order of [Self::walk_instructions], regardless of the instructions'
+    /// nesting level. This helps matching multi-instructions patterns, like an approve
+    /// followed by a transfer, even when they are invoked at different stack heights.
+    ///
+    /// Like [slice::windows], no window is yielded if the transaction has less than `size`
+    /// instructions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn walk_instructions_windows(
+        &self,
+        size: usize,
+    ) -> impl Iterator<Item = Vec<InstructionView<'_>>> + '_ {
+        assert!(size != 0, "window size must be non-zero");
+
+        let mut window = VecDeque::with_capacity(size);
+        self.walk_instructions().filter_map(move |view| {
+            if window.len() == size {
+                window.pop_front();
+            }
+            window.push_back(view);
+
+            if window.len() == size {
+                Some(window.iter().cloned().collect())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn walk_instructions_from(&self, compiled_index: usize) -> AllInstructionIterator<'_> {
+        let message = self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref());
+        let compiled_instruction_count = message.map_or(0, |message| message.instructions.len());
 
         let mut inner_instructions_by_parent = HashMap::new();
         if let Some(meta) = self.meta.as_ref() {
             for inner_instructions in meta.inner_instructions.iter() {
+                if inner_instructions.index as usize >= compiled_instruction_count {
+                    #[cfg(feature = "tracing")]
+                    ::tracing::debug!(
+                        index = inner_instructions.index,
+                        compiled_instruction_count,
+                        "skipping inner instructions with out of range compiled instruction index"
+                    );
+
+                    continue;
+                }
+
                 inner_instructions_by_parent.insert(inner_instructions.index, inner_instructions);
             }
         }
 
         AllInstructionIterator {
             confirmed_transaction: self,
-            message: trx.message.as_ref().unwrap(),
+            message,
             inner_instructions_by_parent,
-            top_level_instruction_index: 0,
+            top_level_instruction_index: compiled_index,
             inner_instruction_index: None,
         }
     }
 
-    pub fn meta(&self) -> Option<&pb::ConfirmedTransaction> {
-        if self.meta.is_none() || self.meta.as_ref().unwrap().meta().is_none() {
-            return None;
+    /// Groups all instructions of the transaction, including inner instructions, by their
+    /// stack height (see [InstructionView::stack_height]), compiled instructions being at
+    /// level 0. Within a level, instructions are kept in [Self::walk_instructions] order.
+    ///
+    /// This gives a view of the CPI fan-out at each depth, complementing the depth-first
+    /// order of [Self::walk_instructions].
+    pub fn instructions_by_level(&self) -> BTreeMap<u32, Vec<InstructionView<'_>>> {
+        let mut levels: BTreeMap<u32, Vec<InstructionView<'_>>> = BTreeMap::new();
+        for view in self.walk_instructions() {
+            levels.entry(view.stack_height()).or_default().push(view);
         }
 
-        return Some(self);
+        levels
     }
-}
 
-struct AllInstructionIterator<'a> {
-    confirmed_transaction: &'a pb::ConfirmedTransaction,
-    message: &'a pb::Message,
-    inner_instructions_by_parent: HashMap<u32, &'a pb::InnerInstructions>,
-    top_level_instruction_index: usize,
-    inner_instruction_index: Option<usize>,
-}
+    /// Returns the deepest stack height (see [InstructionView::stack_height]) reached by the
+    /// instructions of the transaction, a one-number summary of its CPI complexity. It's `0`
+    /// if the transaction has no inner instructions or they have no stack height (old
+    /// blocks).
+    pub fn max_stack_height(&self) -> u32 {
+        self.walk_instructions()
+            .map(|view| view.stack_height())
+            .max()
+            .unwrap_or(0)
+    }
 
-impl<'a> Iterator for AllInstructionIterator<'a> {
-    type Item = InstructionView<'a>;
+    /// Returns `true` if a program is re-entered in any CPI chain of the transaction, that
+    /// is if an instruction's program id is the same as the program id of one of its
+    /// ancestors (including a program invoking itself directly).
+    ///
+    /// The CPI chains are reconstructed from the inner instructions' stack height, see
+    /// [InstructionView::stack_height]. When the stack height is not available (old blocks),
+    /// inner instructions are all considered direct children of their compiled instruction.
+    pub fn has_reentrancy(&self) -> bool {
+        let mut ancestors = InstructionAncestors::new();
+        for view in self.walk_instructions() {
+            let program_id = view.program_id();
+            if ancestors
+                .unwind(&view)
+                .any(|ancestor| *ancestor == program_id)
+            {
+                return true;
+            }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.top_level_instruction_index >= self.message.instructions.len() {
-            return None;
+            ancestors.push(&view, program_id);
         }
 
-        let top_level_instruction = &self.message.instructions[self.top_level_instruction_index];
-        match self.inner_instruction_index {
-            None => {
-                self.inner_instruction_index = Some(0);
-                return Some(InstructionView {
-                    instruction: Box::new(top_level_instruction),
-                    trx: self.confirmed_transaction,
-                    compiled_instruction: top_level_instruction,
-                    compiled_index: Some(self.top_level_instruction_index),
-                });
-            }
-            Some(inner_instruction_index) => {
-                let inner_instructions = self
-                    .inner_instructions_by_parent
-                    .get(&(self.top_level_instruction_index as u32));
-
-                match inner_instructions {
-                    None => {
-                        self.inner_instruction_index = None;
-                        self.top_level_instruction_index += 1;
-                        return self.next();
-                    }
-                    Some(inner_instructions) => {
-                        let inner_instruction_count = inner_instructions.instructions.len();
-                        if inner_instruction_index >= inner_instruction_count {
-                            self.inner_instruction_index = None;
-                            self.top_level_instruction_index += 1;
-                            return self.next();
-                        }
+        false
+    }
+
+    /// Returns the instructions of the transaction, inner instructions included, that are
+    /// identical to a previous one, that is with the same program id, account indices and
+    /// data. Each duplicate is paired with the first instruction it repeats, pairs being in
+    /// [Self::walk_instructions] order of the duplicates.
+    ///
+    /// Repeated identical instructions are unusual and can be worth flagging when looking
+    /// for anomalies, though some are legitimate, e.g. a program invoking the same CPI
+    /// several times.
+    pub fn duplicate_instructions(&self) -> Vec<(InstructionView<'_>, InstructionView<'_>)> {
+        let mut first_seen: HashMap<_, InstructionView<'_>> = HashMap::new();
+        let mut duplicates = Vec::new();
+
+        for view in self.walk_instructions() {
+            let (accounts, data): (&[u8], &[u8]) = match view.instruction {
+                InstructionRef::Compiled(instruction) => (&instruction.accounts, &instruction.data),
+                InstructionRef::Inner(instruction) => (&instruction.accounts, &instruction.data),
+            };
+            let key = (view.program_id().0.as_slice(), accounts, data);
+
+            match first_seen.get(&key) {
+                Some(first) => duplicates.push((first.clone(), view)),
+                None => {
+                    first_seen.insert(key, view);
+                }
+            }
+        }
+
+        duplicates
+    }
+
+    /// Returns the resolved program ids of the transaction's instructions, inner
+    /// instructions included, in [Self::walk_instructions] order, repeats included. This is
+    /// the execution trace of the transaction at the program level, handy to match
+    /// transaction types against known program patterns.
+    pub fn program_sequence(&self) -> Vec<AddressOwned> {
+        self.walk_instructions()
+            .map(|view| view.program_id().into())
+            .collect()
+    }
+
+    /// Groups the instructions of the transaction, inner instructions included, by their
+    /// resolved program id. Within a group, instructions are in [Self::walk_instructions]
+    /// order.
+    pub fn instructions_grouped_by_program(
+        &self,
+    ) -> HashMap<AddressOwned, Vec<InstructionView<'_>>> {
+        let mut groups: HashMap<AddressOwned, Vec<InstructionView<'_>>> = HashMap::new();
+        for view in self.walk_instructions() {
+            groups
+                .entry(view.program_id().into())
+                .or_default()
+                .push(view);
+        }
+
+        groups
+    }
+
+    /// Builds the CPI call graph of the transaction, see [CallGraph] for details about
+    /// its structure. Like [Self::has_reentrancy], the parent links are reconstructed from
+    /// the inner instructions' stack height.
+    pub fn call_graph(&self) -> CallGraph<'_> {
+        let mut nodes: Vec<InstructionNode<'_>> = Vec::new();
+        let mut edges = Vec::new();
+
+        let mut ancestors = InstructionAncestors::new();
+        for view in self.walk_instructions() {
+            let index = nodes.len();
+            let parent = ancestors.unwind(&view).last().copied();
+            if let Some(parent) = parent {
+                edges.push((parent, index));
+            }
+
+            ancestors.push(&view, index);
+            nodes.push(InstructionNode {
+                program_id: view.program_id(),
+                stack_height: view.stack_height(),
+                parent,
+            });
+        }
+
+        CallGraph { nodes, edges }
+    }
+
+    /// Builds the CPI hierarchy of the transaction as a tree, one root per compiled
+    /// instruction with the instructions it invoked as nested children. Like
+    /// [Self::call_graph], the nesting is reconstructed from the inner instructions' stack
+    /// height. Walking the tree depth-first visits the instructions in the same order as
+    /// [Self::walk_instructions].
+    pub fn instruction_tree(&self) -> Vec<InstructionTreeNode<'_>> {
+        let mut roots: Vec<InstructionTreeNode<'_>> = Vec::new();
+
+        let mut ancestors = InstructionAncestors::<usize>::new();
+        for view in self.walk_instructions() {
+            let mut siblings = &mut roots;
+            for &index in ancestors.unwind(&view) {
+                siblings = &mut siblings[index].children;
+            }
+
+            ancestors.push(&view, siblings.len());
+            siblings.push(InstructionTreeNode {
+                view,
+                children: Vec::new(),
+            });
+        }
+
+        roots
+    }
+
+    /// Returns the log lines of the transaction, an empty slice if the transaction has no
+    /// meta.
+    pub fn log_messages(&self) -> &[String] {
+        self.meta
+            .as_ref()
+            .map_or(&[], |meta| meta.log_messages.as_slice())
+    }
+
+    /// Iterates over the payloads of the transaction's `Program data: <payload>` log lines,
+    /// the base64 encoded data emitted through `sol_log_data`, e.g. the Anchor events
+    /// emitted with `emit!`. A line without payload yields an empty string.
+    ///
+    /// The iterator is empty if the transaction has no meta.
+    pub fn program_data_logs(&self) -> impl Iterator<Item = &str> + '_ {
+        self.log_messages()
+            .iter()
+            .filter_map(|line| line.strip_prefix("Program data: "))
+    }
+
+    /// Returns the log lines emitted by each instruction of the transaction, in the same
+    /// order as [Self::walk_instructions], e.g. `logs_by_instruction()[i]` are the logs of
+    /// the `i`-th instruction visited by [Self::walk_instructions].
+    ///
+    /// The association relies on the runtime's `Program <id> invoke [<depth>]` and
+    /// `Program <id> success`/`Program <id> failed: <error>` lines delimiting each
+    /// instruction's frame. An instruction's logs are the lines of its own frame, the
+    /// delimiting lines and the lines of the instructions it invoked being excluded.
+    ///
+    /// When the logs were truncated by the runtime, the result only covers the instructions
+    /// invoked before the truncation and can thus be shorter than the number of instructions.
+    /// The result is empty if the transaction has no meta.
+    pub fn logs_by_instruction(&self) -> Vec<Vec<&str>> {
+        let mut logs: Vec<Vec<&str>> = Vec::new();
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return logs,
+        };
+
+        let mut frames: Vec<usize> = Vec::new();
+        for line in meta.log_messages.iter() {
+            let line = line.as_str();
+            let program_line = line.strip_prefix("Program ");
+
+            if program_line.map_or(false, is_invoke_line) {
+                frames.push(logs.len());
+                logs.push(Vec::new());
+                continue;
+            }
+
+            if program_line.map_or(false, is_frame_end_line) {
+                frames.pop();
+                continue;
+            }
+
+            if let Some(frame) = frames.last() {
+                logs[*frame].push(line);
+            }
+        }
+
+        logs
+    }
+
+    /// Estimates the compute units consumed by each program of the transaction, by parsing
+    /// the runtime's `Program <id> consumed <units> of <limit> compute units` log lines.
+    ///
+    /// The units reported for an invocation include those of the programs it invoked, so
+    /// they are subtracted to attribute to each program only the units it consumed itself,
+    /// and a program invoked multiple times gets the sum of its invocations.
+    ///
+    /// Since it relies on log parsing, the result is incomplete when the logs were truncated
+    /// by the runtime, and builtin programs like the System program, which don't log their
+    /// consumption, are never present. The result is empty if the transaction has no meta.
+    pub fn compute_units_by_program(&self) -> HashMap<AddressOwned, u64> {
+        let mut units: HashMap<AddressOwned, u64> = HashMap::new();
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return units,
+        };
+
+        // The units consumed by the invocations made by each frame
+        let mut frames: Vec<u64> = Vec::new();
+        for line in meta.log_messages.iter() {
+            let program_line = match line.strip_prefix("Program ") {
+                Some(program_line) => program_line,
+                None => continue,
+            };
+
+            if is_invoke_line(program_line) {
+                frames.push(0);
+                continue;
+            }
+
+            if is_frame_end_line(program_line) {
+                frames.pop();
+                continue;
+            }
+
+            let (program_id, consumed) = match parse_consumed_line(program_line) {
+                Some(consumed_line) => consumed_line,
+                None => continue,
+            };
+            let program_id = match base58::decode(program_id) {
+                Ok(program_id) => program_id,
+                Err(_) => continue,
+            };
+
+            let invoked = frames.last().copied().unwrap_or(0);
+            *units.entry(AddressOwned(program_id)).or_default() += consumed.saturating_sub(invoked);
+
+            if frames.len() >= 2 {
+                let caller = frames.len() - 2;
+                frames[caller] += consumed;
+            }
+        }
+
+        units
+    }
+
+    /// Returns `true` if the transaction is mostly about setting up accounts rather than
+    /// operating on them, a coarse classifier to filter out setup noise.
+    ///
+    /// The heuristic is that a strict majority of the top-level instructions are account
+    /// creations, Compute Budget instructions being ignored. The account creations
+    /// considered are the System program's `CreateAccount` and `CreateAccountWithSeed`
+    /// instructions and the Associated Token Account program's `Create` and
+    /// `CreateIdempotent` instructions.
+    pub fn is_setup_heavy(&self) -> bool {
+        let (mut total, mut creations) = (0, 0);
+        for view in self.compiled_instructions() {
+            let program_id = view.program_id();
+            if program_id == COMPUTE_BUDGET_PROGRAM_ID {
+                continue;
+            }
+
+            total += 1;
+            if is_account_creation(&program_id, view.data()) {
+                creations += 1;
+            }
+        }
+
+        creations * 2 > total
+    }
+
+    /// Returns the resolved accounts of the transaction that no instruction, inner
+    /// instructions included, references, neither as an account nor as its program id, in
+    /// [Self::resolved_accounts] order. Useful to spot unused or padding accounts.
+    ///
+    /// Note that the fee payer is commonly unreferenced since paying fees doesn't require an
+    /// instruction. Returns an empty list if the transaction has no message.
+    pub fn unreferenced_accounts(&self) -> Vec<Address<'_>> {
+        if self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .is_none()
+        {
+            return Vec::new();
+        }
+
+        let mut referenced = HashSet::new();
+        for view in self.walk_instructions() {
+            referenced.insert(view.instruction.program_id_index() as usize);
+            referenced.extend(view.account_indices().iter().map(|index| *index as usize));
+        }
+
+        self.resolved_accounts()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !referenced.contains(index))
+            .map(|(_, account)| Address(account))
+            .collect()
+    }
+
+    /// Returns `true` if one of the transaction's top-level (compiled) instructions invokes
+    /// `program_id`, programs invoked only through CPIs don't count. Useful to tell the
+    /// applications the user directly called apart from those invoked on their behalf.
+    pub fn has_top_level_program(&self, program_id: &[u8]) -> bool {
+        self.compiled_instructions()
+            .any(|view| view.program_id().0.as_slice() == program_id)
+    }
+
+    /// Returns the "primary" program of the transaction, a rough label useful to bucket
+    /// transactions by application, using [PrimaryProgramHeuristic::FirstTopLevel]. See
+    /// [Self::primary_program_with] to use another heuristic.
+    pub fn primary_program(&self) -> Option<Address<'_>> {
+        self.primary_program_with(PrimaryProgramHeuristic::FirstTopLevel)
+    }
 
-                        let inner_instruction =
-                            &inner_instructions.instructions[inner_instruction_index];
-                        self.inner_instruction_index = Some(inner_instruction_index + 1);
-                        return Some(InstructionView {
-                            instruction: Box::new(inner_instruction),
-                            trx: self.confirmed_transaction,
-                            compiled_instruction: top_level_instruction,
-                            compiled_index: None,
-                        });
+    /// Returns the "primary" program of the transaction according to `heuristic`, see
+    /// [PrimaryProgramHeuristic] for the available heuristics. Compute Budget instructions
+    /// are always ignored since they are attached to most transactions regardless of
+    /// what they do.
+    ///
+    /// Returns [None] if the transaction has no instruction other than Compute Budget ones.
+    pub fn primary_program_with(&self, heuristic: PrimaryProgramHeuristic) -> Option<Address<'_>> {
+        match heuristic {
+            PrimaryProgramHeuristic::FirstTopLevel => self
+                .compiled_instructions()
+                .map(|view| view.program_id())
+                .find(|program_id| *program_id != COMPUTE_BUDGET_PROGRAM_ID),
+            PrimaryProgramHeuristic::MostInvoked => {
+                let mut counts: Vec<(Address<'_>, usize)> = Vec::new();
+                for view in self.walk_instructions() {
+                    let program_id = view.program_id();
+                    if program_id == COMPUTE_BUDGET_PROGRAM_ID {
+                        continue;
+                    }
+
+                    match counts.iter_mut().find(|(id, _)| *id == program_id) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((program_id, 1)),
                     }
                 }
+
+                // Ties are resolved in favor of the program invoked first
+                let max = counts.iter().map(|(_, count)| *count).max()?;
+                counts
+                    .into_iter()
+                    .find(|(_, count)| *count == max)
+                    .map(|(program_id, _)| program_id)
+            }
+        }
+    }
+
+    /// Returns the transaction's meta, [None] if it's missing.
+    pub fn meta(&self) -> Option<&pb::TransactionStatusMeta> {
+        self.meta.as_ref()
+    }
+
+    /// Returns the execution status of the transaction, [TransactionStatus::Unknown] if the
+    /// transaction has no meta.
+    pub fn status(&self) -> TransactionStatus {
+        match self.meta.as_ref() {
+            Some(meta) if meta.is_failed() => TransactionStatus::Failed,
+            Some(_) => TransactionStatus::Success,
+            None => TransactionStatus::Unknown,
+        }
+    }
+
+    /// Returns `true` if the transaction has a meta and did not fail, see
+    /// [pb::TransactionStatusMeta::is_failed].
+    pub fn is_successful(&self) -> bool {
+        self.meta.as_ref().map_or(false, |meta| !meta.is_failed())
+    }
+
+    /// Returns `true` if the transaction invoked at least one inner instruction, see
+    /// [pb::TransactionStatusMeta::has_inner_instructions]. Returns `false` if the
+    /// transaction has no meta.
+    pub fn has_inner_instructions(&self) -> bool {
+        self.meta
+            .as_ref()
+            .map_or(false, |meta| meta.has_inner_instructions())
+    }
+}
+
+/// The execution status of a transaction, see [pb::ConfirmedTransaction::status].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionStatus {
+    /// The transaction executed successfully.
+    Success,
+    /// The transaction failed, its fee was charged but its other effects were reverted.
+    Failed,
+    /// The transaction has no meta, its status is unknown.
+    Unknown,
+}
+
+/// The heuristic used by [pb::ConfirmedTransaction::primary_program_with] to pick the
+/// "primary" program of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimaryProgramHeuristic {
+    /// The program of the first top-level instruction.
+    FirstTopLevel,
+    /// The program invoked the most times, inner instructions included, ties being resolved
+    /// in favor of the program invoked first.
+    MostInvoked,
+}
+
+/// The CPI call graph of a transaction, see [pb::ConfirmedTransaction::call_graph].
+#[derive(Debug)]
+pub struct CallGraph<'a> {
+    /// The instructions of the transaction, in [pb::ConfirmedTransaction::walk_instructions]
+    /// order.
+    pub nodes: Vec<InstructionNode<'a>>,
+
+    /// The invocations as `(caller, callee)` pairs of indices in [Self::nodes], compiled
+    /// instructions are never callees.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// An instruction of a [CallGraph].
+#[derive(Debug)]
+pub struct InstructionNode<'a> {
+    /// The resolved program id of the instruction.
+    pub program_id: Address<'a>,
+
+    /// The stack height of the instruction, see [InstructionView::stack_height].
+    pub stack_height: u32,
+
+    /// The index in [CallGraph::nodes] of the instruction that invoked this one, [None]
+    /// for compiled instructions.
+    pub parent: Option<usize>,
+}
+
+/// An instruction of the tree built by [pb::ConfirmedTransaction::instruction_tree].
+#[derive(Clone)]
+pub struct InstructionTreeNode<'a> {
+    /// The instruction.
+    pub view: InstructionView<'a>,
+
+    /// The instructions invoked by this one, in invocation order.
+    pub children: Vec<InstructionTreeNode<'a>>,
+}
+
+/// The position of an instruction within its block, see
+/// [pb::Block::walk_instructions_ordered]. Paths order like the instructions they point
+/// to, a compiled instruction being before its inner instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstructionPath {
+    /// The index of the transaction in [pb::Block::transactions], failed transactions
+    /// included.
+    pub transaction_index: usize,
+
+    /// The index of the compiled instruction in the transaction's message, the instruction
+    /// itself or the one holding it if it's an inner instruction.
+    pub compiled_index: usize,
+
+    /// The index of the inner instruction among the inner instructions of its compiled
+    /// instruction, [None] for compiled instructions.
+    pub inner_index: Option<usize>,
+}
+
+/// Tracks the ancestors of instructions visited in [pb::ConfirmedTransaction::walk_instructions]
+/// order, relying on the stack height of inner instructions to reconstruct the CPI nesting.
+/// An inner instruction's parent is the closest previously visited instruction of the same
+/// compiled instruction with a lower stack height, or the compiled instruction itself.
+struct InstructionAncestors<T> {
+    stack: Vec<(u32, T)>,
+}
+
+impl<T> InstructionAncestors<T> {
+    fn new() -> Self {
+        InstructionAncestors { stack: Vec::new() }
+    }
+
+    /// Unwinds the stack up to the parent of `view` and returns the ancestors of `view`,
+    /// starting from its compiled instruction and ending with its direct parent.
+    fn unwind(&mut self, view: &InstructionView) -> impl Iterator<Item = &T> {
+        if view.is_root() {
+            self.stack.clear();
+        } else {
+            let stack_height = view.stack_height();
+            while self.stack.len() > 1 && self.stack[self.stack.len() - 1].0 >= stack_height {
+                self.stack.pop();
+            }
+        }
+
+        self.stack.iter().map(|(_, value)| value)
+    }
+
+    /// Pushes `view`, which must have been unwound first, as the deepest ancestor.
+    fn push(&mut self, view: &InstructionView, value: T) {
+        self.stack.push((view.stack_height(), value));
+    }
+}
+
+struct AllInstructionIterator<'a> {
+    confirmed_transaction: &'a pb::ConfirmedTransaction,
+    message: Option<&'a pb::Message>,
+    inner_instructions_by_parent: HashMap<u32, &'a pb::InnerInstructions>,
+    top_level_instruction_index: usize,
+    inner_instruction_index: Option<usize>,
+}
+
+impl<'a> Iterator for AllInstructionIterator<'a> {
+    type Item = InstructionView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let message = self.message?;
+
+        // Loops instead of recursing when moving to the next compiled instruction, so stack
+        // usage stays constant whatever the number of instructions.
+        loop {
+            if self.top_level_instruction_index >= message.instructions.len() {
+                return None;
+            }
+
+            let top_level_instruction = &message.instructions[self.top_level_instruction_index];
+            let inner_instruction_index = match self.inner_instruction_index {
+                None => {
+                    self.inner_instruction_index = Some(0);
+                    return Some(InstructionView {
+                        instruction: InstructionRef::Compiled(top_level_instruction),
+                        trx: self.confirmed_transaction,
+                        compiled_instruction: top_level_instruction,
+                        compiled_index: Some(self.top_level_instruction_index),
+                    });
+                }
+                Some(inner_instruction_index) => inner_instruction_index,
+            };
+
+            let inner_instruction = self
+                .inner_instructions_by_parent
+                .get(&(self.top_level_instruction_index as u32))
+                .and_then(|inner_instructions| {
+                    inner_instructions.instructions.get(inner_instruction_index)
+                });
+
+            match inner_instruction {
+                None => {
+                    self.inner_instruction_index = None;
+                    self.top_level_instruction_index += 1;
+                }
+                Some(inner_instruction) => {
+                    self.inner_instruction_index = Some(inner_instruction_index + 1);
+                    return Some(InstructionView {
+                        instruction: InstructionRef::Inner(inner_instruction),
+                        trx: self.confirmed_transaction,
+                        compiled_instruction: top_level_instruction,
+                        compiled_index: None,
+                    });
+                }
             }
         }
     }
 }
 
+/// Returns `true` if the instruction is a System `CreateAccount`/`CreateAccountWithSeed` or
+/// an Associated Token Account `Create`/`CreateIdempotent` instruction.
+fn is_account_creation(program_id: &Address, data: &[u8]) -> bool {
+    if *program_id == SYSTEM_PROGRAM_ID {
+        // System instructions are prefixed by their little-endian u32 index
+        return matches!(data.get(..4), Some([0, 0, 0, 0]) | Some([3, 0, 0, 0]));
+    }
+
+    if *program_id == ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID {
+        return matches!(data.first(), None | Some(0) | Some(1));
+    }
+
+    false
+}
+
+/// Returns `true` for `<id> invoke [<depth>]`, the `Program ` prefix being stripped.
+fn is_invoke_line(line: &str) -> bool {
+    match line.split_once(' ') {
+        Some((id, rest)) => {
+            is_program_id_token(id) && rest.starts_with("invoke [") && rest.ends_with(']')
+        }
+        None => false,
+    }
+}
+
+/// Returns `true` for `<id> success` and `<id> failed: <error>`, the `Program ` prefix
+/// being stripped.
+fn is_frame_end_line(line: &str) -> bool {
+    match line.split_once(' ') {
+        Some((id, rest)) => {
+            is_program_id_token(id) && (rest == "success" || rest.starts_with("failed: "))
+        }
+        None => false,
+    }
+}
+
+/// Returns `true` if `token` is made of base58 characters only, like the program id of
+/// the runtime's log lines. This rejects the `log:`, `data:` and `return:` tokens of the
+/// lines logged by programs, which could otherwise spoof the runtime's lines, e.g.
+/// `msg!("success")` logging `Program log: success`.
+fn is_program_id_token(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, b'0' | b'O' | b'I' | b'l'))
+}
+
+/// Parses `<id> consumed <units> of <limit> compute units`, the `Program ` prefix being
+/// stripped, into the program id and the consumed units.
+fn parse_consumed_line(line: &str) -> Option<(&str, u64)> {
+    let (program_id, rest) = line.split_once(" consumed ")?;
+    if !is_program_id_token(program_id) {
+        return None;
+    }
+
+    let (consumed, rest) = rest.split_once(" of ")?;
+    if !rest.ends_with(" compute units") {
+        return None;
+    }
+
+    Some((program_id, consumed.parse().ok()?))
+}
+
 impl pb::TransactionStatusMeta {
+    #[deprecated(
+        note = "conflates failure and inner instructions recording, use `is_failed` and `has_inner_instructions` instead"
+    )]
     pub fn meta(&self) -> Option<&pb::TransactionStatusMeta> {
         if self.err.is_some() || self.inner_instructions_none {
             return None;
         }
-        return Some(self);
+        Some(self)
+    }
+
+    /// Returns `true` if the transaction failed, e.g. if it has an error. Failed transactions
+    /// are still included in blocks, their fee is charged but their other effects are
+    /// reverted.
+    pub fn is_failed(&self) -> bool {
+        self.err.is_some()
+    }
+
+    /// Returns `true` if the transaction invoked at least one inner instruction. Inner
+    /// instructions were not recorded for some old blocks (see the `inner_instructions_none`
+    /// field), in which case this returns `false`.
+    pub fn has_inner_instructions(&self) -> bool {
+        !self.inner_instructions_none && !self.inner_instructions.is_empty()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::LazyLock;
     use std::vec;
 
-    use crate::{block_view::InstructionView, pb::sf::solana::r#type::v1 as pb, Instruction};
+    use crate::{
+        address::{AccountMeta, AddressOwned},
+        base58,
+        block_view::{
+            InstructionRef, InstructionTreeNode, InstructionView, PrimaryProgramHeuristic,
+            TransactionStatus,
+        },
+        compute_budget::COMPUTE_BUDGET_PROGRAM_ID,
+        error::{TransactionErrorKind, TransactionValidationError},
+        pb::sf::solana::r#type::v1 as pb,
+        programs::{ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID},
+        Instruction,
+    };
     use paste::paste;
     use pretty_assertions::assert_eq;
 
@@ -410,53 +1722,454 @@ mod tests {
         assert_eq!(None, iter.next());
     }
 
-    macro_rules! walk_instructions_test_case {
-        ( $name:ident, $trx:expr, $expected:expr ) => {
-            paste! {
-                #[test]
-                fn [<it_instructions_ $name>]() {
-                    let trx = $trx;
-                    let instructions = trx
-                        .walk_instructions()
-                        .map(Into::<ComparableInstructionView>::into)
-                        .collect::<Vec<_>>();
-                    assert_eq!($expected, instructions);
-                }
-            }
+    #[test]
+    fn it_iterates_over_tagged_transactions() {
+        let trx = |id: u8, meta: Option<pb::TransactionStatusMeta>| pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![id]],
+                message: None,
+            }),
+            meta,
+        };
+        let failed = pb::TransactionStatusMeta {
+            err: Some(pb::TransactionError { err: vec![1] }),
+            ..Default::default()
+        };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(1, Some(pb::TransactionStatusMeta::default())),
+                trx(2, Some(failed)),
+                trx(3, None),
+            ],
+            ..Default::default()
         };
+
+        assert_eq!(
+            vec![
+                (TransactionStatus::Success, 1),
+                (TransactionStatus::Failed, 2),
+                (TransactionStatus::Unknown, 3),
+            ],
+            block
+                .transactions_tagged()
+                .map(|(status, trx)| (status, trx.transaction.as_ref().unwrap().signatures[0][0]))
+                .collect::<Vec<_>>()
+        );
     }
 
-    walk_instructions_test_case!(
-        empty_trx,
-        pb::ConfirmedTransaction {
-            transaction: Some(pb::Transaction {
-                signatures: vec![vec![1, 2, 3]],
-                message: Some(pb::Message {
-                    account_keys: vec![hex("00"), hex("01"), hex("02")],
-                    ..Default::default()
-                }),
+    #[test]
+    fn it_returns_block_height() {
+        let block = pb::Block {
+            slot: 250_000_010,
+            block_height: Some(pb::BlockHeight {
+                block_height: 229_000_000,
             }),
-            meta: Some(pb::TransactionStatusMeta {
-                err: Some(pb::TransactionError {
-                    ..Default::default()
-                }),
-                ..Default::default()
+            ..Default::default()
+        };
+
+        assert_eq!(Some(229_000_000), block.block_height());
+        assert_eq!(250_000_010, block.slot);
+
+        let block = pb::Block {
+            slot: 10,
+            block_height: None,
+            ..Default::default()
+        };
+
+        assert_eq!(None, block.block_height());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn it_returns_block_datetime() {
+        let block = pb::Block {
+            block_time: Some(pb::UnixTimestamp {
+                timestamp: 1_700_000_000,
             }),
-        },
-        Vec::<ComparableInstructionView>::new()
-    );
+            ..Default::default()
+        };
 
-    walk_instructions_test_case!(
-        single_top_level_instruction,
-        pb::ConfirmedTransaction {
-            transaction: Some(pb::Transaction {
-                signatures: vec![vec![1, 2, 3]],
-                message: Some(pb::Message {
-                    account_keys: vec![hex("a0"), hex("a1"), hex("a2")],
-                    instructions: vec![pb::CompiledInstruction {
-                        program_id_index: 1,
-                        accounts: vec![0, 1],
-                        data: vec![1, 2, 3],
+        let datetime = block.block_datetime().unwrap();
+        assert_eq!(2023, datetime.year());
+        assert_eq!(time::Month::November, datetime.month());
+        assert_eq!(14, datetime.day());
+        assert_eq!((22, 13, 20), datetime.to_hms());
+        assert_eq!(time::UtcOffset::UTC, datetime.offset());
+
+        assert_eq!(None, pb::Block::default().block_datetime());
+    }
+
+    #[test]
+    fn it_computes_block_byte_size() {
+        let block = pb::Block {
+            blockhash: str("2MJu"),
+            slot: 250_000_000,
+            transactions: vec![full_trx(), full_trx()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            prost::Message::encode_to_vec(&block).len(),
+            block.byte_size()
+        );
+        assert_eq!(0, pb::Block::default().byte_size());
+    }
+
+    #[test]
+    fn it_collects_block_signers() {
+        let trx = |signers: u32, keys: Vec<&str>, err: Option<pb::TransactionError>| {
+            pb::ConfirmedTransaction {
+                transaction: Some(pb::Transaction {
+                    signatures: vec![vec![1, 2, 3]],
+                    message: Some(pb::Message {
+                        header: Some(pb::MessageHeader {
+                            num_required_signatures: signers,
+                            ..Default::default()
+                        }),
+                        account_keys: keys.into_iter().map(hex).collect(),
+                        ..Default::default()
+                    }),
+                }),
+                meta: Some(pb::TransactionStatusMeta {
+                    err,
+                    ..Default::default()
+                }),
+            }
+        };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(2, vec!["a0", "a1", "a2"], None),
+                trx(1, vec!["a1", "a3"], None),
+                trx(1, vec!["a4", "a0"], Some(pb::TransactionError::default())),
+            ],
+            ..Default::default()
+        };
+
+        let mut signers = block
+            .signers()
+            .into_iter()
+            .map(hex::encode)
+            .collect::<Vec<_>>();
+        signers.sort();
+
+        assert_eq!(vec![str("a0"), str("a1")], signers);
+    }
+
+    #[test]
+    fn it_iterates_block_signatures() {
+        let trx = |signatures: Vec<Vec<u8>>, err: Option<pb::TransactionError>| {
+            pb::ConfirmedTransaction {
+                transaction: Some(pb::Transaction {
+                    signatures,
+                    ..Default::default()
+                }),
+                meta: Some(pb::TransactionStatusMeta {
+                    err,
+                    ..Default::default()
+                }),
+            }
+        };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(vec![vec![1, 2, 3], vec![4, 5, 6]], None),
+                trx(vec![vec![7, 8, 9]], Some(pb::TransactionError::default())),
+                trx(vec![vec![10, 11]], None),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![&[1, 2, 3][..], &[7, 8, 9], &[10, 11]],
+            block.signatures().collect::<Vec<_>>()
+        );
+        assert_eq!(0, pb::Block::default().signatures().count());
+    }
+
+    #[test]
+    fn it_finds_transaction_by_signature() {
+        let trx = |signatures: Vec<Vec<u8>>, err: Option<pb::TransactionError>| {
+            pb::ConfirmedTransaction {
+                transaction: Some(pb::Transaction {
+                    signatures,
+                    ..Default::default()
+                }),
+                meta: Some(pb::TransactionStatusMeta {
+                    err,
+                    ..Default::default()
+                }),
+            }
+        };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(vec![vec![1, 2, 3], vec![4, 5, 6]], None),
+                trx(vec![vec![7, 8, 9]], Some(pb::TransactionError::default())),
+                pb::ConfirmedTransaction::default(),
+            ],
+            ..Default::default()
+        };
+
+        assert!(block.contains_signature(&[1, 2, 3]));
+        assert!(block.contains_signature(&[7, 8, 9]));
+        assert!(!block.contains_signature(&[4, 5, 6]));
+        assert!(!block.contains_signature(&[]));
+
+        assert_eq!(
+            Some(&block.transactions[1]),
+            block.transaction_by_signature(&[7, 8, 9])
+        );
+        assert_eq!(None, block.transaction_by_signature(&[1, 2]));
+    }
+
+    #[test]
+    fn it_walks_block_instructions_in_order_with_paths() {
+        let mut failed = full_trx();
+        failed.meta.as_mut().unwrap().err = Some(pb::TransactionError::default());
+
+        let block = pb::Block {
+            transactions: vec![full_trx(), failed, full_trx()],
+            ..Default::default()
+        };
+
+        let walked = block
+            .walk_instructions_ordered()
+            .map(|(path, view)| {
+                (
+                    (
+                        path.transaction_index,
+                        path.compiled_index,
+                        path.inner_index,
+                    ),
+                    hex::encode(view.program_id()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let expected = |transaction_index| {
+            vec![
+                ((transaction_index, 0, None), str("a1")),
+                ((transaction_index, 0, Some(0)), str("a4")),
+                ((transaction_index, 1, None), str("a2")),
+                ((transaction_index, 2, None), str("a3")),
+                ((transaction_index, 2, Some(0)), str("a5")),
+                ((transaction_index, 2, Some(1)), str("a6")),
+            ]
+        };
+        assert_eq!([expected(0), expected(2)].concat(), walked);
+
+        let paths = block
+            .walk_instructions_ordered()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert!(paths.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(block.walk_instructions().count(), paths.len());
+    }
+
+    #[test]
+    fn it_flattens_block_instructions() {
+        let mut failed = full_trx();
+        failed.meta.as_mut().unwrap().err = Some(pb::TransactionError::default());
+
+        let block = pb::Block {
+            slot: 42,
+            transactions: vec![failed, full_trx()],
+            ..Default::default()
+        };
+
+        let rows = block
+            .flat_instructions()
+            .map(|(slot, transaction_index, path, view)| {
+                (
+                    slot,
+                    transaction_index,
+                    (path.compiled_index, path.inner_index),
+                    hex::encode(view.program_id()),
+                    hex::encode(view.data()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (42, 1, (0, None), str("a1"), str("010203")),
+                (42, 1, (0, Some(0)), str("a4"), str("040506")),
+                (42, 1, (1, None), str("a2"), str("060708")),
+                (42, 1, (2, None), str("a3"), str("090a0b")),
+                (42, 1, (2, Some(0)), str("a5"), str("0a0b0c")),
+                (42, 1, (2, Some(1)), str("a6"), str("0d0e0f")),
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn it_filters_transactions_by_fee_payer() {
+        let trx =
+            |id: u8, keys: Vec<&str>, err: Option<pb::TransactionError>| pb::ConfirmedTransaction {
+                transaction: Some(pb::Transaction {
+                    signatures: vec![vec![id]],
+                    message: Some(pb::Message {
+                        header: Some(pb::MessageHeader {
+                            num_required_signatures: 1,
+                            ..Default::default()
+                        }),
+                        account_keys: keys.into_iter().map(hex).collect(),
+                        ..Default::default()
+                    }),
+                }),
+                meta: Some(pb::TransactionStatusMeta {
+                    err,
+                    ..Default::default()
+                }),
+            };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(1, vec!["a0", "a1"], None),
+                trx(2, vec!["a1", "a0"], None),
+                trx(3, vec!["a0"], Some(pb::TransactionError::default())),
+                trx(4, vec![], None),
+            ],
+            ..Default::default()
+        };
+
+        let ids = |payer: &str| {
+            let payer = hex(payer);
+            block
+                .transactions_by_fee_payer(&payer)
+                .map(|trx| trx.transaction.as_ref().unwrap().signatures[0][0])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![1, 3], ids("a0"));
+        assert_eq!(vec![2], ids("a1"));
+        assert!(ids("a2").is_empty());
+        assert_eq!(None, block.transactions[3].fee_payer());
+    }
+
+    #[test]
+    fn it_filters_transactions_failed_with_error_kind() {
+        let trx = |id: u8, err: Option<Vec<u8>>| pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![id]],
+                ..Default::default()
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                err: err.map(|err| pb::TransactionError { err }),
+                ..Default::default()
+            }),
+        };
+
+        let block = pb::Block {
+            transactions: vec![
+                trx(1, None),
+                trx(2, Some(vec![4, 0, 0, 0])),
+                trx(3, Some(vec![8, 0, 0, 0, 0, 1, 0, 0, 0])),
+                trx(4, Some(vec![4, 0, 0, 0])),
+                trx(5, Some(vec![7, 0, 0, 0])),
+            ],
+            ..Default::default()
+        };
+
+        let ids = |kind| {
+            block
+                .transactions_failed_with(kind)
+                .map(|trx| trx.transaction.as_ref().unwrap().signatures[0][0])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![2, 4],
+            ids(TransactionErrorKind::InsufficientFundsForFee)
+        );
+        assert_eq!(vec![3], ids(TransactionErrorKind::InstructionError));
+        assert_eq!(vec![5], ids(TransactionErrorKind::BlockhashNotFound));
+        assert!(ids(TransactionErrorKind::AccountInUse).is_empty());
+    }
+
+    #[test]
+    fn it_summarizes_block_rewards() {
+        let reward = |pubkey: &str, lamports: i64, reward_type: pb::RewardType| pb::Reward {
+            pubkey: pubkey.to_string(),
+            lamports,
+            post_balance: 1_000_000,
+            reward_type: reward_type as i32,
+            commission: "".to_string(),
+        };
+
+        let block = pb::Block {
+            rewards: vec![
+                reward("Ldp", 5_000, pb::RewardType::Fee),
+                reward("2MJu", -100, pb::RewardType::Rent),
+                reward("invalid0", 1, pb::RewardType::Voting),
+                pb::Reward {
+                    reward_type: 42,
+                    ..reward("Ldp", 7, pb::RewardType::Staking)
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![
+                (AddressOwned(vec![1, 2, 3]), 5_000, pb::RewardType::Fee),
+                (AddressOwned(vec![4, 5, 6]), -100, pb::RewardType::Rent),
+                (AddressOwned(vec![1, 2, 3]), 7, pb::RewardType::Unspecified),
+            ],
+            block.reward_summary()
+        );
+    }
+
+    macro_rules! walk_instructions_test_case {
+        ( $name:ident, $trx:expr, $expected:expr ) => {
+            paste! {
+                #[test]
+                fn [<it_instructions_ $name>]() {
+                    let trx = $trx;
+                    let instructions = trx
+                        .walk_instructions()
+                        .map(Into::<ComparableInstructionView>::into)
+                        .collect::<Vec<_>>();
+                    assert_eq!($expected, instructions);
+                }
+            }
+        };
+    }
+
+    walk_instructions_test_case!(
+        empty_trx,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![hex("00"), hex("01"), hex("02")],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                err: Some(pb::TransactionError {
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    walk_instructions_test_case!(
+        single_top_level_instruction,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![hex("a0"), hex("a1"), hex("a2")],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0, 1],
+                        data: vec![1, 2, 3],
                     }],
                     ..Default::default()
                 }),
@@ -475,205 +2188,1374 @@ mod tests {
         }]
     );
 
-    walk_instructions_test_case!(
-        multiple_top_level_instruction,
-        pb::ConfirmedTransaction {
+    walk_instructions_test_case!(
+        multiple_top_level_instruction,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![hex("a0"), hex("a1"), hex("a2")],
+                    instructions: vec![
+                        pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0, 1],
+                            data: vec![1, 2, 3],
+                        },
+                        pb::CompiledInstruction {
+                            program_id_index: 2,
+                            accounts: vec![1, 2],
+                            data: vec![6, 7, 8],
+                        }
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                ..Default::default()
+            }),
+        },
+        vec![
+            ComparableInstructionView {
+                program_id: str("a1"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("010203"),
+                stack_height: 0,
+                instruction_id: 1,
+                compiled_instruction_id: 1,
+            },
+            ComparableInstructionView {
+                program_id: str("a2"),
+                accounts: vec![str("a1"), str("a2")],
+                data: str("060708"),
+                stack_height: 0,
+                instruction_id: 2,
+                compiled_instruction_id: 2,
+            }
+        ]
+    );
+
+    walk_instructions_test_case!(
+        full_deep_nested_instructions,
+        full_trx(),
+        vec![
+            ComparableInstructionView {
+                program_id: str("a1"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("010203"),
+                stack_height: 0,
+                instruction_id: 1,
+                compiled_instruction_id: 1,
+            },
+            ComparableInstructionView {
+                program_id: str("a4"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("040506"),
+                stack_height: 1,
+                instruction_id: 4,
+                compiled_instruction_id: 1,
+            },
+            ComparableInstructionView {
+                program_id: str("a2"),
+                accounts: vec![str("a1"), str("a2")],
+                data: str("060708"),
+                stack_height: 0,
+                instruction_id: 2,
+                compiled_instruction_id: 2,
+            },
+            ComparableInstructionView {
+                program_id: str("a3"),
+                accounts: vec![str("a2")],
+                data: str("090a0b"),
+                stack_height: 0,
+                instruction_id: 3,
+                compiled_instruction_id: 3,
+            },
+            ComparableInstructionView {
+                program_id: str("a5"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("0a0b0c"),
+                stack_height: 1,
+                instruction_id: 5,
+                compiled_instruction_id: 3,
+            },
+            ComparableInstructionView {
+                program_id: str("a6"),
+                accounts: vec![str("a1"), str("a2")],
+                data: str("0d0e0f"),
+                stack_height: 2,
+                instruction_id: 6,
+                compiled_instruction_id: 3,
+            },
+        ]
+    );
+
+    walk_instructions_test_case!(
+        missing_transaction,
+        pb::ConfirmedTransaction {
+            transaction: None,
+            meta: Some(pb::TransactionStatusMeta::default()),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    walk_instructions_test_case!(
+        missing_message,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: None,
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    macro_rules! compiled_instructions_test_case {
+        ( $name:ident, $trx:expr, $expected:expr ) => {
+            paste! {
+                #[test]
+                fn [<it_compiled_instructions_ $name>]() {
+                    let trx = $trx;
+                    let instructions = trx
+                        .compiled_instructions()
+                        .map(Into::<ComparableInstructionView>::into)
+                        .collect::<Vec<_>>();
+                    assert_eq!($expected, instructions);
+                }
+            }
+        };
+    }
+
+    compiled_instructions_test_case!(
+        empty_trx,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![hex("00"), hex("01"), hex("02")],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                err: Some(pb::TransactionError {
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    compiled_instructions_test_case!(
+        missing_transaction,
+        pb::ConfirmedTransaction {
+            transaction: None,
+            meta: Some(pb::TransactionStatusMeta::default()),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    compiled_instructions_test_case!(
+        missing_message,
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: None,
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        },
+        Vec::<ComparableInstructionView>::new()
+    );
+
+    compiled_instructions_test_case!(
+        full_deep_nested_instructions,
+        full_trx(),
+        vec![
+            ComparableInstructionView {
+                program_id: str("a1"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("010203"),
+                stack_height: 0,
+                instruction_id: 1,
+                compiled_instruction_id: 1,
+            },
+            ComparableInstructionView {
+                program_id: str("a2"),
+                accounts: vec![str("a1"), str("a2")],
+                data: str("060708"),
+                stack_height: 0,
+                instruction_id: 2,
+                compiled_instruction_id: 2,
+            },
+            ComparableInstructionView {
+                program_id: str("a3"),
+                accounts: vec![str("a2")],
+                data: str("090a0b"),
+                stack_height: 0,
+                instruction_id: 3,
+                compiled_instruction_id: 3,
+            },
+        ]
+    );
+
+    #[test]
+    fn it_compiled_instructions_with_cpis() {
+        let trx = full_trx();
+        let instructions = trx
+            .compiled_instructions_with_cpis()
+            .map(Into::<ComparableInstructionView>::into)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ComparableInstructionView {
+                    program_id: str("a1"),
+                    accounts: vec![str("a0"), str("a1")],
+                    data: str("010203"),
+                    stack_height: 0,
+                    instruction_id: 1,
+                    compiled_instruction_id: 1,
+                },
+                ComparableInstructionView {
+                    program_id: str("a3"),
+                    accounts: vec![str("a2")],
+                    data: str("090a0b"),
+                    stack_height: 0,
+                    instruction_id: 3,
+                    compiled_instruction_id: 3,
+                },
+            ],
+            instructions
+        );
+    }
+
+    #[test]
+    pub fn compiled_instruction_inner_instruction() {
+        let trx = full_trx();
+
+        let view = trx.compiled_instructions().nth(2).unwrap();
+
+        assert_eq!(
+            ComparableInstructionView {
+                program_id: str("a5"),
+                accounts: vec![str("a0"), str("a1")],
+                data: str("0a0b0c"),
+                stack_height: 1,
+                instruction_id: 5,
+                compiled_instruction_id: 3,
+            },
+            view.inner_instruction(0).unwrap().into()
+        );
+
+        assert_eq!(
+            ComparableInstructionView {
+                program_id: str("a6"),
+                accounts: vec![str("a1"), str("a2")],
+                data: str("0d0e0f"),
+                stack_height: 2,
+                instruction_id: 6,
+                compiled_instruction_id: 3,
+            },
+            view.inner_instruction(1).unwrap().into()
+        );
+
+        assert_eq!(true, view.inner_instruction(2).is_none());
+    }
+
+    #[test]
+    fn it_returns_root_data_of_inner_instructions() {
+        let trx = full_trx();
+        let views = trx.walk_instructions().collect::<Vec<_>>();
+
+        // a5 and a6 are inner instructions (a6 being nested at stack height 2) of a3
+        for view in &views[4..6] {
+            assert_eq!(false, view.is_root());
+            assert_eq!("090a0b", hex::encode(view.root_data()));
+            assert_eq!("090a0b", hex::encode(view.compiled_instruction().data()));
+        }
+
+        assert_eq!("090a0b", hex::encode(views[3].root_data()));
+    }
+
+    #[test]
+    fn it_walks_compiled_instruction_subtree() {
+        let trx = full_trx();
+        let program_ids = |compiled_index: usize| {
+            trx.walk_subtree(compiled_index)
+                .map(|view| hex::encode(view.program_id()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![str("a3"), str("a5"), str("a6")], program_ids(2));
+        assert_eq!(vec![str("a2")], program_ids(1));
+        assert_eq!(vec![str("a1"), str("a4")], program_ids(0));
+        assert_eq!(Vec::<String>::new(), program_ids(3));
+    }
+
+    #[test]
+    fn it_returns_max_stack_height() {
+        assert_eq!(2, full_trx().max_stack_height());
+
+        let mut flat = full_trx();
+        flat.meta.as_mut().unwrap().inner_instructions.clear();
+        assert_eq!(0, flat.max_stack_height());
+
+        let block = pb::Block {
+            transactions: vec![flat.clone(), full_trx()],
+            ..Default::default()
+        };
+        assert_eq!(2, block.max_stack_height());
+        assert_eq!(0, pb::Block::default().max_stack_height());
+    }
+
+    #[test]
+    fn it_skips_out_of_range_inner_instructions() {
+        assert_eq!(Ok(()), full_trx().validate());
+
+        let mut trx = full_trx();
+        trx.meta
+            .as_mut()
+            .unwrap()
+            .inner_instructions
+            .push(pb::InnerInstructions {
+                index: 3,
+                instructions: vec![pb::InnerInstruction {
+                    program_id_index: 6,
+                    accounts: vec![0],
+                    data: vec![1],
+                    stack_height: Some(2),
+                }],
+            });
+
+        assert_eq!(
+            Err(
+                TransactionValidationError::InnerInstructionsIndexOutOfRange {
+                    index: 3,
+                    compiled_instruction_count: 3,
+                }
+            ),
+            trx.validate()
+        );
+        assert_eq!(
+            vec![
+                str("a1"),
+                str("a4"),
+                str("a2"),
+                str("a3"),
+                str("a5"),
+                str("a6")
+            ],
+            trx.walk_instructions()
+                .map(|view| hex::encode(view.program_id()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(3, trx.compiled_instructions().count());
+    }
+
+    #[test]
+    fn it_walks_instructions_bounded() {
+        let trx = full_trx();
+        let program_ids = |max_depth: u32| {
+            trx.walk_instructions_bounded(max_depth)
+                .map(|view| hex::encode(view.program_id()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![str("a1"), str("a2"), str("a3")], program_ids(0));
+        assert_eq!(
+            vec![str("a1"), str("a4"), str("a2"), str("a3"), str("a5")],
+            program_ids(1)
+        );
+        assert_eq!(trx.walk_instructions().count(), program_ids(2).len());
+    }
+
+    #[test]
+    fn it_walks_many_instructions_without_inner_instructions() {
+        let count = 100_000;
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![hex("a0"), hex("a1")],
+                    instructions: (0..count)
+                        .map(|i| pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data: (i as u32).to_le_bytes().to_vec(),
+                        })
+                        .collect(),
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                inner_instructions: vec![pb::InnerInstructions {
+                    index: count as u32 - 1,
+                    instructions: vec![],
+                }],
+                ..Default::default()
+            }),
+        };
+
+        let mut walked = 0;
+        for (i, view) in trx.walk_instructions().enumerate() {
+            assert_eq!(Some(i), view.compiled_index);
+            walked += 1;
+        }
+
+        assert_eq!(count, walked);
+    }
+
+    #[test]
+    fn it_returns_instruction_account_indices() {
+        let trx = full_trx();
+        let indices = trx
+            .walk_instructions()
+            .map(|view| view.account_indices().to_vec())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                vec![0, 1],
+                vec![0, 1],
+                vec![1, 2],
+                vec![2],
+                vec![0, 1],
+                vec![1, 2]
+            ],
+            indices
+        );
+    }
+
+    #[test]
+    fn it_compares_instruction_data() {
+        const EXPECTED: [u8; 3] = [6, 7, 8];
+
+        let trx = full_trx();
+        let views = trx.compiled_instructions().collect::<Vec<_>>();
+
+        assert!(views[1].data_equals(&EXPECTED));
+        assert!(!views[0].data_equals(&EXPECTED));
+        assert!(!views[1].data_equals(&EXPECTED[..2]));
+        assert!(!views[1].data_equals(&[]));
+    }
+
+    #[test]
+    fn it_iterates_instruction_accounts_lazily() {
+        let trx = full_trx();
+
+        for view in trx.walk_instructions() {
+            assert_eq!(view.accounts(), view.accounts_iter().collect::<Vec<_>>());
+        }
+
+        let view = trx.compiled_instructions().nth(1).unwrap();
+        assert_eq!(
+            vec![str("a1"), str("a2")],
+            view.accounts_iter().map(hex::encode).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_exposes_underlying_proto_instruction() {
+        let trx = full_trx();
+        let message = trx.transaction.as_ref().unwrap().message.as_ref().unwrap();
+        let meta = trx.meta.as_ref().unwrap();
+        let views = trx.walk_instructions().collect::<Vec<_>>();
+
+        assert_eq!(Some(&message.instructions[0]), views[0].as_compiled());
+        assert_eq!(None, views[0].as_inner());
+
+        assert_eq!(None, views[1].as_compiled());
+        assert_eq!(
+            Some(&meta.inner_instructions[0].instructions[0]),
+            views[1].as_inner()
+        );
+        assert_eq!(
+            Some(&message.instructions[0]),
+            views[1].compiled_instruction().as_compiled()
+        );
+    }
+
+    #[test]
+    fn it_returns_primary_program() {
+        let instruction = |program_id_index: u32| pb::CompiledInstruction {
+            program_id_index,
+            accounts: vec![0],
+            data: vec![],
+        };
+        let inner_instruction = |program_id_index: u32| pb::InnerInstruction {
+            program_id_index,
+            accounts: vec![0],
+            data: vec![],
+            stack_height: Some(2),
+        };
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![
+                        hex("a0"),
+                        COMPUTE_BUDGET_PROGRAM_ID.to_vec(),
+                        hex("b1"),
+                        hex("b2"),
+                    ],
+                    instructions: vec![
+                        instruction(1),
+                        instruction(1),
+                        instruction(2),
+                        instruction(3),
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                inner_instructions: vec![pb::InnerInstructions {
+                    index: 2,
+                    instructions: vec![inner_instruction(3), inner_instruction(3)],
+                }],
+                ..Default::default()
+            }),
+        };
+
+        assert_eq!(
+            Some(hex("b1")),
+            trx.primary_program().map(|id| id.0.clone())
+        );
+        assert_eq!(
+            Some(hex("b2")),
+            trx.primary_program_with(PrimaryProgramHeuristic::MostInvoked)
+                .map(|id| id.0.clone())
+        );
+
+        let mut compute_budget_only = trx.clone();
+        compute_budget_only
+            .transaction
+            .as_mut()
+            .unwrap()
+            .message
+            .as_mut()
+            .unwrap()
+            .instructions
+            .truncate(2);
+        compute_budget_only.meta = Some(pb::TransactionStatusMeta::default());
+
+        assert!(compute_budget_only.primary_program().is_none());
+        assert!(compute_budget_only
+            .primary_program_with(PrimaryProgramHeuristic::MostInvoked)
+            .is_none());
+    }
+
+    #[test]
+    fn it_returns_transaction_account_metas() {
+        let trx = full_trx();
+        let view = trx.walk_instructions().nth(1).unwrap();
+
+        let metas = |metas: Vec<AccountMeta>| {
+            metas
+                .into_iter()
+                .map(|meta| (meta.address.0.clone(), meta.is_signer, meta.is_writable))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(7, view.transaction_account_metas().len());
+        assert_eq!(
+            metas(trx.account_metas()),
+            metas(view.transaction_account_metas())
+        );
+    }
+
+    #[test]
+    fn it_resolves_instruction_account_writability() {
+        let mut trx = full_trx();
+        trx.transaction
+            .as_mut()
+            .unwrap()
+            .message
+            .as_mut()
+            .unwrap()
+            .header = Some(pb::MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 5,
+        });
+
+        // Accounts a1 (writable) and a2 (readonly)
+        let view = trx.compiled_instructions().nth(1).unwrap();
+
+        assert_eq!(Some(true), view.is_account_writable(0));
+        assert_eq!(Some(false), view.is_account_writable(1));
+        assert_eq!(None, view.is_account_writable(2));
+    }
+
+    #[test]
+    fn it_estimates_compute_units_by_program() {
+        let (token, ata, budget) = (
+            base58::encode(TOKEN_PROGRAM_ID),
+            base58::encode(ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID),
+            base58::encode(COMPUTE_BUDGET_PROGRAM_ID),
+        );
+
+        let mut trx = full_trx();
+        trx.meta.as_mut().unwrap().log_messages = vec![
+            format!("Program {} invoke [1]", budget),
+            format!("Program {} success", budget),
+            format!("Program {} invoke [1]", ata),
+            str("Program log: Create"),
+            format!("Program {} invoke [2]", token),
+            // Spoofed lines logged by the program itself
+            str("Program log: success"),
+            str("Program log: invoke [3]"),
+            str("Program log: x consumed 5000 of 10000 compute units"),
+            format!("Program {} consumed 3000 of 190000 compute units", token),
+            format!("Program {} success", token),
+            str("Program 11111111111111111111111111111111 invoke [2]"),
+            str("Program 11111111111111111111111111111111 success"),
+            format!("Program {} consumed 20000 of 200000 compute units", ata),
+            format!("Program {} success", ata),
+            format!("Program {} invoke [1]", token),
+            format!("Program {} consumed 4500 of 180000 compute units", token),
+            format!("Program {} failed: custom program error: 0x1", token),
+        ];
+
+        let mut units = trx
+            .compute_units_by_program()
+            .into_iter()
+            .map(|(program_id, units)| (program_id.to_string(), units))
+            .collect::<Vec<_>>();
+        units.sort();
+
+        assert_eq!(vec![(ata, 17_000), (token, 7_500)], units);
+
+        trx.meta = None;
+        assert!(trx.compute_units_by_program().is_empty());
+    }
+
+    fn logged_trx() -> pb::ConfirmedTransaction {
+        let mut trx = full_trx();
+        trx.meta.as_mut().unwrap().log_messages = vec![
+            str("Program a1 invoke [1]"),
+            str("Program log: a1 start"),
+            str("Program a4 invoke [2]"),
+            str("Program log: a4"),
+            str("Program a4 success"),
+            str("Program log: a1 end"),
+            str("Program a1 consumed 100 of 200000 compute units"),
+            str("Program a1 success"),
+            str("Program a2 invoke [1]"),
+            str("Program a2 success"),
+            str("Program a3 invoke [1]"),
+            str("Program a5 invoke [2]"),
+            str("Program a6 invoke [3]"),
+            str("Program data: AQID"),
+            str("Program a6 failed: custom program error: 0x1"),
+            str("Program log: a5"),
+            str("Program a5 success"),
+            str("Program a3 success"),
+        ];
+        trx
+    }
+
+    #[test]
+    fn it_returns_program_data_logs() {
+        let mut trx = logged_trx();
+        assert_eq!(18, trx.log_messages().len());
+        assert_eq!(vec!["AQID"], trx.program_data_logs().collect::<Vec<_>>());
+
+        let log_messages = &mut trx.meta.as_mut().unwrap().log_messages;
+        log_messages.push(str("Program data: "));
+        log_messages.push(str("Program data:"));
+        log_messages.push(str("Program log: Program data: BAUG"));
+        assert_eq!(
+            vec!["AQID", ""],
+            trx.program_data_logs().collect::<Vec<_>>()
+        );
+
+        trx.meta = None;
+        assert!(trx.log_messages().is_empty());
+        assert_eq!(0, trx.program_data_logs().count());
+    }
+
+    #[test]
+    fn it_ignores_spoofed_frame_lines_in_logs() {
+        let mut trx = logged_trx();
+        let log_messages = &mut trx.meta.as_mut().unwrap().log_messages;
+        log_messages.insert(2, str("Program log: success"));
+        log_messages.insert(3, str("Program log: invoke [1]"));
+        log_messages.insert(4, str("Program log: failed: nope"));
+        log_messages.insert(5, str("Program data: invoke [2]"));
+
+        let logs = trx.logs_by_instruction();
+        assert_eq!(
+            vec![
+                "Program log: a1 start",
+                "Program log: success",
+                "Program log: invoke [1]",
+                "Program log: failed: nope",
+                "Program data: invoke [2]",
+                "Program log: a1 end",
+                "Program a1 consumed 100 of 200000 compute units"
+            ],
+            logs[0]
+        );
+        assert_eq!(logged_trx().logs_by_instruction()[1..], logs[1..]);
+    }
+
+    #[test]
+    fn it_associates_logs_to_instructions() {
+        let trx = logged_trx();
+
+        assert_eq!(
+            vec![
+                vec![
+                    "Program log: a1 start",
+                    "Program log: a1 end",
+                    "Program a1 consumed 100 of 200000 compute units"
+                ],
+                vec!["Program log: a4"],
+                vec![],
+                vec![],
+                vec!["Program log: a5"],
+                vec!["Program data: AQID"],
+            ],
+            trx.logs_by_instruction()
+        );
+
+        let mut truncated = logged_trx();
+        truncated.meta.as_mut().unwrap().log_messages.truncate(4);
+        truncated
+            .meta
+            .as_mut()
+            .unwrap()
+            .log_messages
+            .push(str("Log truncated"));
+
+        assert_eq!(
+            vec![
+                vec!["Program log: a1 start"],
+                vec!["Program log: a4", "Log truncated"]
+            ],
+            truncated.logs_by_instruction()
+        );
+    }
+
+    #[test]
+    fn it_iterates_block_instruction_logs() {
+        let block = pb::Block {
+            transactions: vec![logged_trx()],
+            ..Default::default()
+        };
+
+        let logs = block
+            .instruction_logs()
+            .map(|(trx, view, logs)| {
+                (
+                    trx.id(),
+                    hex::encode(view.program_id()),
+                    logs.into_iter().map(str).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(6, logs.len());
+        assert_eq!(
+            (str("Ldp"), str("a4"), vec![str("Program log: a4")]),
+            logs[1]
+        );
+        assert_eq!((str("Ldp"), str("a2"), vec![]), logs[2]);
+    }
+
+    #[test]
+    fn it_compares_instructions() {
+        let mut trx = full_trx();
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.instructions.push(message.instructions[0].clone());
+
+        let views = trx.compiled_instructions().collect::<Vec<_>>();
+        assert!(views[0].same_instruction(&views[0]));
+        assert!(views[0].same_instruction(&views[3]));
+        assert!(!views[0].same_instruction(&views[1]));
+
+        let other_trx = full_trx();
+        let other = other_trx.compiled_instructions().next().unwrap();
+        assert!(views[3].same_instruction(&other));
+
+        let inner = other_trx.walk_instructions().nth(1).unwrap();
+        assert!(!other.same_instruction(&inner));
+    }
+
+    #[test]
+    fn it_matches_program_against_many_ids() {
+        let trx = full_trx();
+        let view = trx.compiled_instructions().nth(1).unwrap();
+        let (a1, a2, a3) = (hex("a1"), hex("a2"), hex("a3"));
+
+        assert!(view.program_is_one_of_slices(&[&a1, &a2]));
+        assert!(!view.program_is_one_of_slices(&[&a1, &a3]));
+        assert!(!view.program_is_one_of_slices(&[]));
+
+        assert!(!view.program_is_one_of(&[[0xa2; 32], [0xa1; 32]]));
+        assert!(!view.program_is_one_of(&[]));
+
+        let mut trx = full_trx();
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.account_keys[2] = vec![0xa2; 32];
+
+        let view = trx.compiled_instructions().nth(1).unwrap();
+        assert!(view.program_is_one_of(&[[0xa1; 32], [0xa2; 32]]));
+        assert!(!view.program_is_one_of(&[[0xa1; 32], [0xa3; 32]]));
+    }
+
+    #[test]
+    fn it_returns_data_discriminator() {
+        let trx = full_trx();
+        let view = trx.compiled_instructions().next().unwrap();
+
+        assert_eq!(Some(&[1u8][..]), view.discriminator(1));
+        assert_eq!(Some(&[1u8, 2, 3][..]), view.discriminator(3));
+        assert_eq!(Some(&[][..]), view.discriminator(0));
+        assert_eq!(None, view.discriminator(8));
+        assert_eq!(None, view.anchor_discriminator());
+        assert_eq!(Some(1), view.variant_tag());
+
+        let data = [175, 175, 109, 31, 13, 152, 155, 237, 1];
+        let mut anchor_trx = full_trx();
+        anchor_trx
+            .transaction
+            .as_mut()
+            .unwrap()
+            .message
+            .as_mut()
+            .unwrap()
+            .instructions[0]
+            .data = data.to_vec();
+        let view = anchor_trx.compiled_instructions().next().unwrap();
+
+        assert_eq!(Some(&data[..8]), view.discriminator(8));
+        assert_eq!(data[..8].try_into().ok(), view.anchor_discriminator());
+        assert!(matches!(view.discriminator(1), Some([175])));
+    }
+
+    #[test]
+    fn it_returns_maybe_message() {
+        let trx = full_trx();
+        let view = trx.compiled_instructions().next().unwrap();
+        assert_eq!(
+            trx.transaction.as_ref().unwrap().message.as_ref(),
+            view.maybe_message()
+        );
+
+        // Views are never created over a transaction without message when iterating, build
+        // one by hand to exercise the defensive path.
+        let instruction = pb::CompiledInstruction::default();
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: None,
+            }),
+            meta: None,
+        };
+        let view = InstructionView {
+            instruction: InstructionRef::Compiled(&instruction),
+            trx: &trx,
+            compiled_instruction: &instruction,
+            compiled_index: Some(0),
+        };
+
+        assert_eq!(None, view.maybe_message());
+    }
+
+    #[test]
+    fn it_detects_setup_heavy_transactions() {
+        let trx = |instructions: Vec<(u32, Vec<u8>)>| pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![
+                        hex("a0"),
+                        SYSTEM_PROGRAM_ID.to_vec(),
+                        ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID.to_vec(),
+                        COMPUTE_BUDGET_PROGRAM_ID.to_vec(),
+                        hex("b0"),
+                    ],
+                    instructions: instructions
+                        .into_iter()
+                        .map(|(program_id_index, data)| pb::CompiledInstruction {
+                            program_id_index,
+                            accounts: vec![0],
+                            data,
+                        })
+                        .collect(),
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        let create_account = (1, vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let transfer = (1, vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let ata_create = (2, vec![1]);
+        let compute_budget = (3, vec![2, 0x40, 0x0d, 0x03, 0x00]);
+        let swap = (4, vec![9, 9]);
+
+        assert!(trx(vec![
+            compute_budget.clone(),
+            compute_budget.clone(),
+            create_account.clone(),
+            ata_create.clone(),
+            swap.clone(),
+        ])
+        .is_setup_heavy());
+
+        assert!(!trx(vec![
+            compute_budget.clone(),
+            ata_create.clone(),
+            swap.clone(),
+            transfer.clone(),
+        ])
+        .is_setup_heavy());
+        assert!(!trx(vec![create_account, swap]).is_setup_heavy());
+        assert!(!trx(vec![compute_budget]).is_setup_heavy());
+    }
+
+    #[test]
+    fn it_walks_instructions_windows() {
+        let trx = full_trx();
+        let windows = |size: usize| {
+            trx.walk_instructions_windows(size)
+                .map(|window| {
+                    window
+                        .iter()
+                        .map(|view| hex::encode(view.program_id()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![
+                vec![str("a1"), str("a4")],
+                vec![str("a4"), str("a2")],
+                vec![str("a2"), str("a3")],
+                vec![str("a3"), str("a5")],
+                vec![str("a5"), str("a6")],
+            ],
+            windows(2)
+        );
+        assert_eq!(6, windows(1).len());
+        assert_eq!(1, windows(6).len());
+        assert!(windows(7).is_empty());
+    }
+
+    #[test]
+    fn it_reports_meta_status() {
+        let meta = full_trx().meta.clone().unwrap();
+        assert!(!meta.is_failed());
+        assert!(meta.has_inner_instructions());
+
+        let failed = pb::TransactionStatusMeta {
+            err: Some(pb::TransactionError { err: vec![1] }),
+            ..meta.clone()
+        };
+        assert!(failed.is_failed());
+        assert!(failed.has_inner_instructions());
+
+        let no_inner = pb::TransactionStatusMeta {
+            inner_instructions: vec![],
+            ..meta.clone()
+        };
+        assert!(!no_inner.is_failed());
+        assert!(!no_inner.has_inner_instructions());
+
+        let not_recorded = pb::TransactionStatusMeta {
+            inner_instructions_none: true,
+            ..meta
+        };
+        assert!(!not_recorded.has_inner_instructions());
+    }
+
+    #[test]
+    fn it_returns_transaction_meta() {
+        let trx = full_trx();
+        assert_eq!(trx.meta.as_ref(), trx.meta());
+        assert_eq!(
+            Some(2),
+            trx.meta().map(|meta| meta.inner_instructions.len())
+        );
+        assert!(trx.is_successful());
+        assert!(trx.has_inner_instructions());
+
+        let mut failed = full_trx();
+        failed.meta.as_mut().unwrap().err = Some(pb::TransactionError { err: vec![1] });
+        assert_eq!(failed.meta.as_ref(), failed.meta());
+        assert!(!failed.is_successful());
+
+        let mut no_meta = full_trx();
+        no_meta.meta = None;
+        assert_eq!(None, no_meta.meta());
+        assert!(!no_meta.is_successful());
+        assert!(!no_meta.has_inner_instructions());
+    }
+
+    #[test]
+    fn it_collects_instruction_account_set() {
+        let trx = full_trx();
+        let view = trx.compiled_instructions().next().unwrap();
+
+        let set = view.account_set();
+        assert_eq!(2, set.len());
+        assert_eq!(true, set.contains(hex("a0").as_slice()));
+        assert_eq!(true, set.contains(hex("a1").as_slice()));
+        assert_eq!(false, set.contains(hex("a2").as_slice()));
+    }
+
+    #[cfg(feature = "solana-program")]
+    #[test]
+    fn it_converts_to_solana_instruction() {
+        use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![vec![0; 32], vec![1; 32], vec![2; 32]],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 2,
+                        accounts: vec![1, 0],
+                        data: vec![1, 2, 3],
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        let instruction = trx
+            .compiled_instructions()
+            .next()
+            .unwrap()
+            .to_solana_instruction();
+
+        assert_eq!(
+            solana_program::instruction::Instruction {
+                program_id: Pubkey::new_from_array([2; 32]),
+                accounts: vec![
+                    AccountMeta::new(Pubkey::new_from_array([1; 32]), false),
+                    AccountMeta::new(Pubkey::new_from_array([0; 32]), true),
+                ],
+                data: vec![1, 2, 3],
+            },
+            instruction
+        );
+    }
+
+    #[test]
+    fn it_groups_instructions_by_level() {
+        let trx = full_trx();
+        let levels = trx
+            .instructions_by_level()
+            .into_iter()
+            .map(|(level, views)| {
+                let program_ids = views
+                    .iter()
+                    .map(|view| hex::encode(view.program_id()))
+                    .collect::<Vec<_>>();
+
+                (level, program_ids)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (0, vec![str("a1"), str("a2"), str("a3")]),
+                (1, vec![str("a4"), str("a5")]),
+                (2, vec![str("a6")]),
+            ],
+            levels
+        );
+    }
+
+    #[test]
+    fn it_finds_duplicate_instructions() {
+        assert!(full_trx().duplicate_instructions().is_empty());
+
+        let instruction =
+            |program_id_index: u32, accounts: Vec<u8>, data: Vec<u8>| pb::CompiledInstruction {
+                program_id_index,
+                accounts,
+                data,
+            };
+
+        let trx = pb::ConfirmedTransaction {
             transaction: Some(pb::Transaction {
                 signatures: vec![vec![1, 2, 3]],
                 message: Some(pb::Message {
                     account_keys: vec![hex("a0"), hex("a1"), hex("a2")],
                     instructions: vec![
-                        pb::CompiledInstruction {
-                            program_id_index: 1,
-                            accounts: vec![0, 1],
-                            data: vec![1, 2, 3],
-                        },
-                        pb::CompiledInstruction {
-                            program_id_index: 2,
-                            accounts: vec![1, 2],
-                            data: vec![6, 7, 8],
-                        }
+                        instruction(1, vec![0], vec![1, 2]),
+                        instruction(1, vec![0], vec![1, 3]),
+                        instruction(2, vec![0], vec![1, 2]),
+                        instruction(1, vec![0], vec![1, 2]),
+                        instruction(1, vec![0, 2], vec![1, 2]),
                     ],
                     ..Default::default()
                 }),
             }),
-            meta: Some(pb::TransactionStatusMeta {
-                ..Default::default()
-            }),
-        },
-        vec![
-            ComparableInstructionView {
-                program_id: str("a1"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("010203"),
-                stack_height: 0,
-                instruction_id: 1,
-                compiled_instruction_id: 1,
-            },
-            ComparableInstructionView {
-                program_id: str("a2"),
-                accounts: vec![str("a1"), str("a2")],
-                data: str("060708"),
-                stack_height: 0,
-                instruction_id: 2,
-                compiled_instruction_id: 2,
-            }
-        ]
-    );
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
 
-    walk_instructions_test_case!(
-        full_deep_nested_instructions,
-        FULL_TRX.clone(),
-        vec![
-            ComparableInstructionView {
-                program_id: str("a1"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("010203"),
-                stack_height: 0,
-                instruction_id: 1,
-                compiled_instruction_id: 1,
-            },
-            ComparableInstructionView {
-                program_id: str("a4"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("040506"),
-                stack_height: 1,
-                instruction_id: 4,
-                compiled_instruction_id: 1,
-            },
-            ComparableInstructionView {
-                program_id: str("a2"),
-                accounts: vec![str("a1"), str("a2")],
-                data: str("060708"),
-                stack_height: 0,
-                instruction_id: 2,
-                compiled_instruction_id: 2,
-            },
-            ComparableInstructionView {
-                program_id: str("a3"),
-                accounts: vec![str("a2")],
-                data: str("090a0b"),
-                stack_height: 0,
-                instruction_id: 3,
-                compiled_instruction_id: 3,
-            },
-            ComparableInstructionView {
-                program_id: str("a5"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("0a0b0c"),
-                stack_height: 1,
-                instruction_id: 5,
-                compiled_instruction_id: 3,
-            },
-            ComparableInstructionView {
-                program_id: str("a6"),
-                accounts: vec![str("a1"), str("a2")],
-                data: str("0d0e0f"),
-                stack_height: 2,
-                instruction_id: 6,
-                compiled_instruction_id: 3,
-            },
-        ]
-    );
+        let duplicates = trx.duplicate_instructions();
 
-    macro_rules! compiled_instructions_test_case {
-        ( $name:ident, $trx:expr, $expected:expr ) => {
-            paste! {
-                #[test]
-                fn [<it_compiled_instructions_ $name>]() {
-                    let trx = $trx;
-                    let instructions = trx
-                        .compiled_instructions()
-                        .map(Into::<ComparableInstructionView>::into)
-                        .collect::<Vec<_>>();
-                    assert_eq!($expected, instructions);
-                }
-            }
-        };
+        assert_eq!(1, duplicates.len());
+        let (first, duplicate) = &duplicates[0];
+        assert_eq!(Some(0), first.compiled_index);
+        assert_eq!(Some(3), duplicate.compiled_index);
     }
 
-    compiled_instructions_test_case!(
-        empty_trx,
-        pb::ConfirmedTransaction {
+    #[test]
+    fn it_returns_program_sequence() {
+        let mut trx = full_trx();
+        trx.meta.as_mut().unwrap().inner_instructions[1].instructions[1].program_id_index = 3;
+
+        assert_eq!(
+            vec![
+                str("a1"),
+                str("a4"),
+                str("a2"),
+                str("a3"),
+                str("a5"),
+                str("a3")
+            ],
+            trx.program_sequence()
+                .iter()
+                .map(hex::encode)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                str("a1"),
+                str("a4"),
+                str("a2"),
+                str("a3"),
+                str("a5"),
+                str("a6")
+            ],
+            full_trx()
+                .program_sequence()
+                .iter()
+                .map(hex::encode)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_groups_instructions_by_program() {
+        let mut trx = full_trx();
+        trx.meta.as_mut().unwrap().inner_instructions[1].instructions[1].program_id_index = 3;
+
+        let groups = trx.instructions_grouped_by_program();
+
+        let mut summary = groups
+            .iter()
+            .map(|(program_id, views)| {
+                (
+                    hex::encode(program_id),
+                    views.iter().map(|view| hex::encode(view.data())).collect(),
+                )
+            })
+            .collect::<Vec<(String, Vec<String>)>>();
+        summary.sort();
+
+        assert_eq!(
+            vec![
+                (str("a1"), vec![str("010203")]),
+                (str("a2"), vec![str("060708")]),
+                (str("a3"), vec![str("090a0b"), str("0d0e0f")]),
+                (str("a4"), vec![str("040506")]),
+                (str("a5"), vec![str("0a0b0c")]),
+            ],
+            summary
+        );
+    }
+
+    #[test]
+    fn it_detects_reentrancy() {
+        assert_eq!(false, full_trx().has_reentrancy());
+
+        let reentrant = |inner_program_ids: Vec<(u32, u32)>| pb::ConfirmedTransaction {
             transaction: Some(pb::Transaction {
                 signatures: vec![vec![1, 2, 3]],
                 message: Some(pb::Message {
-                    account_keys: vec![hex("00"), hex("01"), hex("02")],
+                    account_keys: vec![hex("a0"), hex("a1"), hex("a2"), hex("a3")],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0],
+                        data: vec![],
+                    }],
                     ..Default::default()
                 }),
             }),
             meta: Some(pb::TransactionStatusMeta {
-                err: Some(pb::TransactionError {
-                    ..Default::default()
-                }),
+                inner_instructions: vec![pb::InnerInstructions {
+                    index: 0,
+                    instructions: inner_program_ids
+                        .into_iter()
+                        .map(|(program_id_index, stack_height)| pb::InnerInstruction {
+                            program_id_index,
+                            accounts: vec![0],
+                            data: vec![],
+                            stack_height: Some(stack_height),
+                        })
+                        .collect(),
+                }],
                 ..Default::default()
             }),
-        },
-        Vec::<ComparableInstructionView>::new()
-    );
+        };
 
-    compiled_instructions_test_case!(
-        full_deep_nested_instructions,
-        FULL_TRX.clone(),
-        vec![
-            ComparableInstructionView {
-                program_id: str("a1"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("010203"),
-                stack_height: 0,
-                instruction_id: 1,
-                compiled_instruction_id: 1,
-            },
-            ComparableInstructionView {
-                program_id: str("a2"),
-                accounts: vec![str("a1"), str("a2")],
-                data: str("060708"),
-                stack_height: 0,
-                instruction_id: 2,
-                compiled_instruction_id: 2,
-            },
-            ComparableInstructionView {
-                program_id: str("a3"),
-                accounts: vec![str("a2")],
-                data: str("090a0b"),
-                stack_height: 0,
-                instruction_id: 3,
-                compiled_instruction_id: 3,
-            },
-        ]
-    );
+        // a1 -> a2 -> a1
+        assert_eq!(true, reentrant(vec![(2, 1), (1, 2)]).has_reentrancy());
+        // a1 -> a2 -> a3 -> a2
+        assert_eq!(
+            true,
+            reentrant(vec![(2, 1), (3, 2), (2, 3)]).has_reentrancy()
+        );
+        // a1 -> a1
+        assert_eq!(true, reentrant(vec![(1, 1)]).has_reentrancy());
+        // a1 -> (a2 -> a3, a2 -> a3), siblings are not ancestors
+        assert_eq!(
+            false,
+            reentrant(vec![(2, 1), (3, 2), (2, 1), (3, 2)]).has_reentrancy()
+        );
+    }
 
     #[test]
-    pub fn compiled_instruction_inner_instruction() {
-        let trx = FULL_TRX.clone();
+    fn it_builds_call_graph() {
+        let trx = full_trx();
+        let graph = trx.call_graph();
 
-        let view = trx.compiled_instructions().nth(2).unwrap();
+        assert_eq!(vec![(0, 1), (3, 4), (4, 5)], graph.edges);
+        assert_eq!(
+            vec![
+                (str("a1"), 0, None),
+                (str("a4"), 1, Some(0)),
+                (str("a2"), 0, None),
+                (str("a3"), 0, None),
+                (str("a5"), 1, Some(3)),
+                (str("a6"), 2, Some(4)),
+            ],
+            graph
+                .nodes
+                .iter()
+                .map(|node| (
+                    hex::encode(&node.program_id),
+                    node.stack_height,
+                    node.parent
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_resolves_parent_and_children_from_stack_height() {
+        let trx = full_trx();
+        let views = trx.walk_instructions().collect::<Vec<_>>();
+        let ids = |views: Vec<InstructionView>| {
+            views
+                .iter()
+                .map(|view| hex::encode(view.program_id()))
+                .collect::<Vec<_>>()
+        };
+
+        let (a1, a3, a5, a6) = (&views[0], &views[3], &views[4], &views[5]);
+
+        let parent = a6.parent().unwrap();
+        assert_eq!(str("a5"), hex::encode(parent.program_id()));
+        assert!(!parent.is_root());
+
+        let parent = a5.parent().unwrap();
+        assert_eq!(str("a3"), hex::encode(parent.program_id()));
+        assert!(parent.is_root());
+        assert_eq!(None, parent.parent().map(|view| view.program_id()));
 
+        assert_eq!(vec![str("a4")], ids(a1.children()));
+        assert_eq!(vec![str("a5")], ids(a3.children()));
+        assert_eq!(vec![str("a6")], ids(a5.children()));
+        assert!(a6.children().is_empty());
+
+        assert_eq!(vec![str("a5"), str("a3")], ids(a6.ancestors().collect()));
+        assert_eq!(vec![str("a1")], ids(views[1].ancestors().collect()));
+        assert_eq!(0, a3.ancestors().count());
         assert_eq!(
-            ComparableInstructionView {
-                program_id: str("a5"),
-                accounts: vec![str("a0"), str("a1")],
-                data: str("0a0b0c"),
-                stack_height: 1,
-                instruction_id: 5,
-                compiled_instruction_id: 3,
-            },
-            view.inner_instruction(0).unwrap().into()
+            vec![str("a5"), str("a6")],
+            ids(a3.inner_instructions().collect())
         );
+    }
+
+    #[test]
+    fn it_finds_unreferenced_accounts() {
+        assert!(full_trx().unreferenced_accounts().is_empty());
+
+        let mut trx = full_trx();
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.account_keys.push(hex("a7"));
+        message.instructions[0].accounts = vec![1];
+        let meta = trx.meta.as_mut().unwrap();
+        meta.loaded_writable_addresses = vec![hex("b0")];
+        meta.loaded_readonly_addresses = vec![hex("b1")];
+        meta.inner_instructions[0].instructions[0].accounts.push(8);
 
         assert_eq!(
-            ComparableInstructionView {
-                program_id: str("a6"),
-                accounts: vec![str("a1"), str("a2")],
-                data: str("0d0e0f"),
-                stack_height: 2,
-                instruction_id: 6,
-                compiled_instruction_id: 3,
-            },
-            view.inner_instruction(1).unwrap().into()
+            vec![str("a7"), str("b1")],
+            trx.unreferenced_accounts()
+                .into_iter()
+                .map(hex::encode)
+                .collect::<Vec<_>>()
         );
+        assert!(pb::ConfirmedTransaction::default()
+            .unreferenced_accounts()
+            .is_empty());
+    }
 
-        assert_eq!(true, view.inner_instruction(2).is_none());
+    #[test]
+    fn it_checks_top_level_program() {
+        let trx = full_trx();
+
+        assert!(trx.has_top_level_program(&hex("a1")));
+        assert!(trx.has_top_level_program(&hex("a3")));
+        // Only invoked through CPIs
+        assert!(!trx.has_top_level_program(&hex("a4")));
+        assert!(!trx.has_top_level_program(&hex("a6")));
+        assert!(!trx.has_top_level_program(&hex("b0")));
+    }
+
+    #[test]
+    fn it_builds_instruction_tree() {
+        fn render(nodes: &[InstructionTreeNode]) -> String {
+            nodes
+                .iter()
+                .map(|node| {
+                    let program_id = hex::encode(node.view.program_id());
+                    match node.children.is_empty() {
+                        true => program_id,
+                        false => format!("{}({})", program_id, render(&node.children)),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let trx = full_trx();
+        let tree = trx.instruction_tree();
+
+        assert_eq!("a1(a4), a2, a3(a5(a6))", render(&tree));
+        assert_eq!(vec![10, 11, 12], *tree[2].children[0].view.data());
+        assert!(pb::ConfirmedTransaction::default()
+            .instruction_tree()
+            .is_empty());
     }
 
     #[derive(Debug, PartialEq)]
@@ -709,8 +3591,8 @@ mod tests {
         ::hex::decode(s).unwrap()
     }
 
-    static FULL_TRX: LazyLock<pb::ConfirmedTransaction> =
-        LazyLock::new(|| pb::ConfirmedTransaction {
+    fn full_trx() -> pb::ConfirmedTransaction {
+        pb::ConfirmedTransaction {
             transaction: Some(pb::Transaction {
                 signatures: vec![vec![1, 2, 3]],
                 message: Some(pb::Message {
@@ -774,5 +3656,6 @@ mod tests {
                 ],
                 ..Default::default()
             }),
-        });
+        }
+    }
 }
@@ -0,0 +1,271 @@
+//! Helpers around the Compute Budget program, used by transactions to request a compute unit
+//! limit and set a compute unit price (priority fee).
+
+use substreams_solana_macro::b58;
+
+use crate::{
+    block_view::InstructionView, cursor::DataCursor,
+    pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+/// The Compute Budget program id, `ComputeBudget111111111111111111111111111111`.
+pub const COMPUTE_BUDGET_PROGRAM_ID: [u8; 32] = b58!("ComputeBudget111111111111111111111111111111");
+
+const REQUEST_HEAP_FRAME_DISCRIMINATOR: u8 = 1;
+const SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR: u8 = 3;
+const SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT_DISCRIMINATOR: u8 = 4;
+
+/// A Compute Budget program instruction, see [InstructionView::compute_budget_instruction].
+///
+/// The deprecated `RequestUnits` instruction (discriminator `0`) is not supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComputeBudgetInstruction {
+    /// Requests a specific transaction-wide program heap region size, in bytes.
+    RequestHeapFrame(u32),
+    /// Sets the transaction-wide compute unit limit.
+    SetComputeUnitLimit(u32),
+    /// Sets the compute unit price, in micro-lamports, used to compute the priority fee.
+    SetComputeUnitPrice(u64),
+    /// Sets the maximum size, in bytes, of the accounts data the transaction may load.
+    SetLoadedAccountsDataSizeLimit(u32),
+}
+
+impl InstructionView<'_> {
+    /// Decodes the instruction as a Compute Budget program instruction, [None] if the
+    /// instruction is not one.
+    ///
+    /// The instruction's data is the borsh encoding of the instruction, a `u8` discriminator
+    /// followed by the little-endian value, a `u64` for `SetComputeUnitPrice` and a `u32`
+    /// for the others.
+    pub fn compute_budget_instruction(&self) -> Option<ComputeBudgetInstruction> {
+        if self.program_id() != COMPUTE_BUDGET_PROGRAM_ID {
+            return None;
+        }
+
+        let mut cursor = self.cursor();
+        let discriminator = cursor.read_u8()?;
+        if !(REQUEST_HEAP_FRAME_DISCRIMINATOR..=SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT_DISCRIMINATOR)
+            .contains(&discriminator)
+        {
+            return None;
+        }
+
+        let decoded = decode_compute_budget_instruction(discriminator, cursor);
+        if decoded.is_none() {
+            trace_decode_failure!(self, "Compute Budget");
+        }
+
+        decoded
+    }
+}
+
+fn decode_compute_budget_instruction(
+    discriminator: u8,
+    mut cursor: DataCursor,
+) -> Option<ComputeBudgetInstruction> {
+    use ComputeBudgetInstruction::*;
+
+    Some(match discriminator {
+        REQUEST_HEAP_FRAME_DISCRIMINATOR => RequestHeapFrame(cursor.read_u32_le()?),
+        SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR => SetComputeUnitLimit(cursor.read_u32_le()?),
+        SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR => SetComputeUnitPrice(cursor.read_u64_le()?),
+        SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT_DISCRIMINATOR => {
+            SetLoadedAccountsDataSizeLimit(cursor.read_u32_le()?)
+        }
+        _ => return None,
+    })
+}
+
+/// The split of a transaction's fee between its base and priority parts, see
+/// [ConfirmedTransaction::fee_breakdown].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    /// The total fee paid by the transaction, in lamports.
+    pub total: u64,
+    /// The base (signatures) part of the fee, in lamports.
+    pub base: u64,
+    /// The priority part of the fee, in lamports.
+    pub priority: u64,
+}
+
+impl ConfirmedTransaction {
+    /// Returns the compute unit price, in micro-lamports, set by the transaction through the
+    /// Compute Budget program's `SetComputeUnitPrice` instruction, [None] if the transaction
+    /// does not set it. Only top-level instructions are considered, like the runtime does.
+    pub fn compute_unit_price(&self) -> Option<u64> {
+        self.compute_budget_instructions()
+            .into_iter()
+            .find_map(|instruction| match instruction {
+                ComputeBudgetInstruction::SetComputeUnitPrice(price) => Some(price),
+                _ => None,
+            })
+    }
+
+    /// Decodes the Compute Budget program instructions of the transaction, in order, see
+    /// [InstructionView::compute_budget_instruction]. Only top-level instructions are
+    /// considered, like the runtime does.
+    pub fn compute_budget_instructions(&self) -> Vec<ComputeBudgetInstruction> {
+        self.compiled_instructions()
+            .filter_map(|view| view.compute_budget_instruction())
+            .collect()
+    }
+
+    /// Splits the transaction's fee between its base and priority parts.
+    ///
+    /// The priority part is derived as `compute_unit_price * compute_units_consumed`,
+    /// converted from micro-lamports to lamports and rounded up, see
+    /// [Self::compute_unit_price]. The base part is the remainder of the total fee,
+    /// clamped at zero. This is an approximation: the runtime computes the priority fee from
+    /// the requested compute unit limit, not the consumed units, and the base fee from the
+    /// number of signatures, so the split is exact only when the transaction requested
+    /// exactly the compute units it consumed.
+    ///
+    /// Returns an all zero breakdown if the transaction has no meta.
+    pub fn fee_breakdown(&self) -> FeeBreakdown {
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return FeeBreakdown::default(),
+        };
+
+        let price = self.compute_unit_price().unwrap_or(0) as u128;
+        let consumed = meta.compute_units_consumed.unwrap_or(0) as u128;
+        let priority = ((price * consumed + 999_999) / 1_000_000).min(meta.fee as u128) as u64;
+
+        FeeBreakdown {
+            total: meta.fee,
+            base: meta.fee - priority,
+            priority,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComputeBudgetInstruction, FeeBreakdown, COMPUTE_BUDGET_PROGRAM_ID};
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    fn budget_trx(price: Option<u64>, fee: u64, consumed: Option<u64>) -> pb::ConfirmedTransaction {
+        let mut instructions = vec![pb::CompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![],
+            data: [vec![2], 200_000u32.to_le_bytes().to_vec()].concat(),
+        }];
+        if let Some(price) = price {
+            instructions.push(pb::CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![],
+                data: [vec![3], price.to_le_bytes().to_vec()].concat(),
+            });
+        }
+        instructions.push(pb::CompiledInstruction {
+            program_id_index: 2,
+            accounts: vec![0],
+            data: vec![3, 1, 0, 0, 0, 0, 0, 0, 0],
+        });
+
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0], COMPUTE_BUDGET_PROGRAM_ID.to_vec(), vec![0xa2]],
+                    instructions,
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                fee,
+                compute_units_consumed: consumed,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn it_computes_fee_breakdown() {
+        // 50_000 micro-lamports * 150_000 CU = 7_500 lamports
+        let trx = budget_trx(Some(50_000), 12_500, Some(150_000));
+        assert_eq!(Some(50_000), trx.compute_unit_price());
+        assert_eq!(
+            FeeBreakdown {
+                total: 12_500,
+                base: 5_000,
+                priority: 7_500,
+            },
+            trx.fee_breakdown()
+        );
+
+        // 1 micro-lamport * 1_500 CU = 0.0015 lamports, rounded up
+        let trx = budget_trx(Some(1), 5_000, Some(1_500));
+        assert_eq!(1, trx.fee_breakdown().priority);
+
+        // Priority is clamped to the total fee
+        let trx = budget_trx(Some(u64::MAX), 5_000, Some(1_400_000));
+        assert_eq!(
+            FeeBreakdown {
+                total: 5_000,
+                base: 0,
+                priority: 5_000,
+            },
+            trx.fee_breakdown()
+        );
+
+        let trx = budget_trx(None, 5_000, Some(150_000));
+        assert_eq!(None, trx.compute_unit_price());
+        assert_eq!(
+            FeeBreakdown {
+                total: 5_000,
+                base: 5_000,
+                priority: 0,
+            },
+            trx.fee_breakdown()
+        );
+
+        let mut trx = budget_trx(Some(50_000), 12_500, Some(150_000));
+        trx.meta = None;
+        assert_eq!(FeeBreakdown::default(), trx.fee_breakdown());
+    }
+
+    #[test]
+    fn it_decodes_compute_budget_instructions() {
+        let instruction = |program_id_index: u32, data: Vec<u8>| pb::CompiledInstruction {
+            program_id_index,
+            accounts: vec![],
+            data,
+        };
+        let with_value = |discriminator: u8, value: &[u8]| [&[discriminator], value].concat();
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0], COMPUTE_BUDGET_PROGRAM_ID.to_vec(), vec![0xa2]],
+                    instructions: vec![
+                        instruction(1, with_value(1, &(256 * 1024u32).to_le_bytes())),
+                        instruction(1, with_value(2, &300_000u32.to_le_bytes())),
+                        instruction(2, with_value(2, &1u32.to_le_bytes())),
+                        instruction(1, with_value(3, &25_000u64.to_le_bytes())),
+                        instruction(1, with_value(4, &65_536u32.to_le_bytes())),
+                        // Deprecated RequestUnits, unknown discriminator and truncated data
+                        instruction(1, with_value(0, &[0; 8])),
+                        instruction(1, with_value(9, &[0; 8])),
+                        instruction(1, with_value(3, &[0; 4])),
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        assert_eq!(
+            vec![
+                ComputeBudgetInstruction::RequestHeapFrame(256 * 1024),
+                ComputeBudgetInstruction::SetComputeUnitLimit(300_000),
+                ComputeBudgetInstruction::SetComputeUnitPrice(25_000),
+                ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(65_536),
+            ],
+            trx.compute_budget_instructions()
+        );
+        assert_eq!(Some(25_000), trx.compute_unit_price());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::block_view::InstructionView;
+
+/// A cursor over an instruction's data, reading fixed size fields in sequence. Each read
+/// advances the cursor past the field, or returns [None] leaving the cursor untouched if
+/// there are not enough bytes left.
+///
+/// ```
+/// # use substreams_solana_core::cursor::DataCursor;
+/// let data = [3, 0x10, 0x27, 0, 0, 0, 0, 0, 0, 9];
+/// let mut cursor = DataCursor::new(&data);
+///
+/// assert_eq!(Some(3), cursor.read_u8());
+/// assert_eq!(Some(10_000), cursor.read_u64_le());
+/// assert_eq!(None, cursor.read_u16_le());
+/// assert_eq!(&[9], cursor.remaining());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DataCursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> DataCursor<'a> {
+    /// Creates a new cursor positioned at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        DataCursor { data, offset: 0 }
+    }
+
+    /// Returns the current offset of the cursor within the data.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bytes that have not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+
+    /// Reads the next `n` bytes.
+    pub fn read_bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let end = self.offset.checked_add(n)?;
+        let bytes = self.data.get(self.offset..end)?;
+        self.offset = end;
+
+        Some(bytes)
+    }
+
+    /// Reads the next byte.
+    pub fn read_u8(&mut self) -> Option<u8> {
+        self.read_array::<1>().map(|bytes| bytes[0])
+    }
+
+    /// Reads the next 2 bytes as a little-endian `u16`.
+    pub fn read_u16_le(&mut self) -> Option<u16> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    /// Reads the next 4 bytes as a little-endian `u32`.
+    pub fn read_u32_le(&mut self) -> Option<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    /// Reads the next 8 bytes as a little-endian `u64`.
+    pub fn read_u64_le(&mut self) -> Option<u64> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    /// Reads the next 32 bytes as a public key.
+    pub fn read_pubkey(&mut self) -> Option<&'a [u8; 32]> {
+        self.read_bytes(32)
+            .map(|bytes| bytes.try_into().expect("read 32 bytes"))
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.read_bytes(N)
+            .map(|bytes| bytes.try_into().expect("read N bytes"))
+    }
+}
+
+impl InstructionView<'_> {
+    /// Returns a [DataCursor] positioned at the start of the instruction's data.
+    pub fn cursor(&self) -> DataCursor<'_> {
+        DataCursor::new(self.data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    #[test]
+    fn it_reads_instruction_data_fields() {
+        let mut data = vec![7u8];
+        data.extend_from_slice(&0x0102u16.to_le_bytes());
+        data.extend_from_slice(&0x03040506u32.to_le_bytes());
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&[0xaa, 0xbb]);
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0], vec![0xa1]],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0],
+                        data,
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        let view = trx.compiled_instructions().next().unwrap();
+        let mut cursor = view.cursor();
+
+        assert_eq!(Some(7), cursor.read_u8());
+        assert_eq!(Some(0x0102), cursor.read_u16_le());
+        assert_eq!(Some(0x03040506), cursor.read_u32_le());
+        assert_eq!(Some(1_000_000_000), cursor.read_u64_le());
+        assert_eq!(Some(&[9; 32]), cursor.read_pubkey());
+        assert_eq!(47, cursor.offset());
+
+        assert_eq!(None, cursor.read_u32_le());
+        assert_eq!(47, cursor.offset());
+        assert_eq!(Some(&[0xaa, 0xbb][..]), cursor.read_bytes(2));
+        assert_eq!(None, cursor.read_u8());
+        assert!(cursor.remaining().is_empty());
+    }
+}
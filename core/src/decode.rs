@@ -0,0 +1,128 @@
+use crate::{block_view::InstructionView, pb::sf::solana::r#type::v1::ConfirmedTransaction};
+
+/// An instruction type that can be decoded from an [InstructionView], see
+/// [ConfirmedTransaction::decode_program_instructions].
+///
+/// The lifetime lets decoded instructions borrow from the transaction, like resolved
+/// [crate::address::Address] accounts.
+///
+/// ```
+/// # use substreams_solana_core::{block_view::InstructionView, decode::DecodableInstruction};
+/// struct Transfer {
+///     amount: u64,
+/// }
+///
+/// impl DecodableInstruction<'_> for Transfer {
+///     fn decode(view: &InstructionView) -> Option<Self> {
+///         let mut cursor = view.cursor();
+///         if cursor.read_u8()? != 3 {
+///             return None;
+///         }
+///
+///         Some(Transfer {
+///             amount: cursor.read_u64_le()?,
+///         })
+///     }
+/// }
+/// ```
+pub trait DecodableInstruction<'a>: Sized {
+    /// Decodes the instruction, [None] if it's not an instruction of this type.
+    fn decode(view: &InstructionView<'a>) -> Option<Self>;
+}
+
+impl ConfirmedTransaction {
+    /// Decodes all the instructions of the transaction, inner instructions included, invoking
+    /// `program_id` as `D`, in [Self::walk_instructions] order. The instructions `D` fails to
+    /// decode are skipped.
+    pub fn decode_program_instructions<'a, D: DecodableInstruction<'a>>(
+        &'a self,
+        program_id: &'a [u8],
+    ) -> impl Iterator<Item = D> + 'a {
+        self.walk_instructions()
+            .filter(move |view| view.program_id().0.as_slice() == program_id)
+            .filter_map(|view| D::decode(&view))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecodableInstruction;
+    use crate::{address::Address, block_view::InstructionView, pb::sf::solana::r#type::v1 as pb};
+
+    #[derive(Debug, PartialEq)]
+    enum Counter<'a> {
+        Increment { counter: Address<'a>, by: u32 },
+        Reset { counter: Address<'a> },
+    }
+
+    impl<'a> DecodableInstruction<'a> for Counter<'a> {
+        fn decode(view: &InstructionView<'a>) -> Option<Self> {
+            let counter = view.accounts_iter().next()?;
+            let mut cursor = view.cursor();
+
+            match cursor.read_u8()? {
+                0 => Some(Counter::Increment {
+                    counter,
+                    by: cursor.read_u32_le()?,
+                }),
+                1 => Some(Counter::Reset { counter }),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn it_decodes_program_instructions() {
+        let instruction = |program_id_index: u32, data: Vec<u8>| pb::CompiledInstruction {
+            program_id_index,
+            accounts: vec![0],
+            data,
+        };
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0], vec![0xa1], vec![0xa2]],
+                    instructions: vec![
+                        instruction(1, vec![0, 5, 0, 0, 0]),
+                        instruction(2, vec![0, 7, 0, 0, 0]),
+                        instruction(1, vec![9]),
+                        instruction(2, vec![1]),
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                inner_instructions: vec![pb::InnerInstructions {
+                    index: 1,
+                    instructions: vec![pb::InnerInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0],
+                        data: vec![1],
+                        stack_height: Some(2),
+                    }],
+                }],
+                ..Default::default()
+            }),
+        };
+
+        let counter = vec![0xa0];
+        let decoded = trx
+            .decode_program_instructions::<Counter>(&[0xa1])
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                Counter::Increment {
+                    counter: Address(&counter),
+                    by: 5
+                },
+                Counter::Reset {
+                    counter: Address(&counter)
+                },
+            ],
+            decoded
+        );
+    }
+}
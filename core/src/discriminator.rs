@@ -0,0 +1,83 @@
+use crate::block_view::InstructionView;
+
+/// A matcher over the leading bytes of an instruction's data, like the single byte
+/// discriminator of SPL programs or the 8 bytes discriminator of Anchor programs.
+///
+/// The constructor is a `const fn` so matchers can be defined as constants and grouped
+/// in tables:
+///
+/// ```
+/// # use substreams_solana_core::discriminator::Discriminator;
+/// const INITIALIZE: Discriminator = Discriminator::new(&[175, 175, 109, 31, 13, 152, 155, 237]);
+/// const TRANSFER: Discriminator = Discriminator::new(&[3]);
+///
+/// static MATCHERS: &[(&str, Discriminator)] = &[("initialize", INITIALIZE), ("transfer", TRANSFER)];
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Discriminator {
+    bytes: &'static [u8],
+}
+
+impl Discriminator {
+    /// Creates a new matcher expecting the instruction's data to start with `bytes`.
+    pub const fn new(bytes: &'static [u8]) -> Self {
+        Discriminator { bytes }
+    }
+
+    /// Returns the expected leading bytes.
+    pub fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Returns `true` if the instruction's data starts with the expected leading bytes.
+    pub fn matches(&self, view: &InstructionView) -> bool {
+        view.data().starts_with(self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Discriminator;
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    // Anchor discriminator of `initialize`, e.g. `sha256("global:initialize")[..8]`
+    const INITIALIZE: Discriminator = Discriminator::new(&[175, 175, 109, 31, 13, 152, 155, 237]);
+
+    #[test]
+    fn it_matches_anchor_discriminator() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0], vec![0xa1]],
+                    instructions: vec![
+                        pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data: vec![175, 175, 109, 31, 13, 152, 155, 237, 1, 0, 0, 0],
+                        },
+                        pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data: vec![175, 175, 109, 31, 13, 152, 155, 238, 1, 0, 0, 0],
+                        },
+                        pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data: vec![175, 175, 109],
+                        },
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        let matches = trx
+            .compiled_instructions()
+            .map(|view| INITIALIZE.matches(&view))
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![true, false, false], matches);
+    }
+}
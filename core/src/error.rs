@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::pb::sf::solana::r#type::v1 as pb;
+
+/// The error returned by [pb::ConfirmedTransaction::account_at_result] when the requested
+/// account index is out of the bounds of the transaction's resolved accounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountIndexError {
+    /// The requested account index.
+    pub index: u8,
+    /// The number of resolved accounts of the transaction, valid indices are `0..len`.
+    pub len: usize,
+}
+
+impl fmt::Display for AccountIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "account index {} out of bounds, transaction has {} accounts",
+            self.index, self.len
+        )
+    }
+}
+
+impl std::error::Error for AccountIndexError {}
+
+/// An inconsistency of a transaction's data, see
+/// [pb::ConfirmedTransaction::validate]. Only corrupted data can fail the validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionValidationError {
+    /// A group of inner instructions refers to a compiled instruction that doesn't exist.
+    InnerInstructionsIndexOutOfRange {
+        /// The compiled instruction index of the inner instructions group.
+        index: u32,
+        /// The number of compiled instructions of the transaction, valid indices are
+        /// `0..compiled_instruction_count`.
+        compiled_instruction_count: usize,
+    },
+}
+
+impl fmt::Display for TransactionValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionValidationError::InnerInstructionsIndexOutOfRange {
+                index,
+                compiled_instruction_count,
+            } => write!(
+                f,
+                "inner instructions index {} out of range, transaction has {} compiled instructions",
+                index, compiled_instruction_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransactionValidationError {}
+
+/// The kind of error a transaction failed with, decoded from the bincode encoded
+/// `TransactionError` enum of the Solana runtime stored in [pb::TransactionError::err].
+///
+/// Only the variant is decoded, payloads like the failing instruction's index of
+/// [TransactionErrorKind::InstructionError] are not. Variants unknown to this version of the
+/// library are reported as [TransactionErrorKind::Unknown] with their raw variant index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionErrorKind {
+    AccountInUse,
+    AccountLoadedTwice,
+    AccountNotFound,
+    ProgramAccountNotFound,
+    InsufficientFundsForFee,
+    InvalidAccountForFee,
+    AlreadyProcessed,
+    BlockhashNotFound,
+    InstructionError,
+    CallChainTooDeep,
+    MissingSignatureForFee,
+    InvalidAccountIndex,
+    SignatureFailure,
+    InvalidProgramForExecution,
+    SanitizeFailure,
+    ClusterMaintenance,
+    AccountBorrowOutstanding,
+    WouldExceedMaxBlockCostLimit,
+    UnsupportedVersion,
+    InvalidWritableAccount,
+    WouldExceedMaxAccountCostLimit,
+    WouldExceedAccountDataBlockLimit,
+    TooManyAccountLocks,
+    AddressLookupTableNotFound,
+    InvalidAddressLookupTableOwner,
+    InvalidAddressLookupTableData,
+    InvalidAddressLookupTableIndex,
+    InvalidRentPayingAccount,
+    WouldExceedMaxVoteCostLimit,
+    WouldExceedAccountDataTotalLimit,
+    DuplicateInstruction,
+    InsufficientFundsForRent,
+    MaxLoadedAccountsDataSizeExceeded,
+    InvalidLoadedAccountsDataSizeLimit,
+    ResanitizationNeeded,
+    ProgramExecutionTemporarilyRestricted,
+    UnbalancedTransaction,
+    ProgramCacheHitMaxLimit,
+    /// A variant unknown to this version of the library, or undecodable error bytes in
+    /// which case the index is `u32::MAX`.
+    Unknown(u32),
+}
+
+impl TransactionErrorKind {
+    fn from_variant_index(index: u32) -> Self {
+        use TransactionErrorKind::*;
+
+        match index {
+            0 => AccountInUse,
+            1 => AccountLoadedTwice,
+            2 => AccountNotFound,
+            3 => ProgramAccountNotFound,
+            4 => InsufficientFundsForFee,
+            5 => InvalidAccountForFee,
+            6 => AlreadyProcessed,
+            7 => BlockhashNotFound,
+            8 => InstructionError,
+            9 => CallChainTooDeep,
+            10 => MissingSignatureForFee,
+            11 => InvalidAccountIndex,
+            12 => SignatureFailure,
+            13 => InvalidProgramForExecution,
+            14 => SanitizeFailure,
+            15 => ClusterMaintenance,
+            16 => AccountBorrowOutstanding,
+            17 => WouldExceedMaxBlockCostLimit,
+            18 => UnsupportedVersion,
+            19 => InvalidWritableAccount,
+            20 => WouldExceedMaxAccountCostLimit,
+            21 => WouldExceedAccountDataBlockLimit,
+            22 => TooManyAccountLocks,
+            23 => AddressLookupTableNotFound,
+            24 => InvalidAddressLookupTableOwner,
+            25 => InvalidAddressLookupTableData,
+            26 => InvalidAddressLookupTableIndex,
+            27 => InvalidRentPayingAccount,
+            28 => WouldExceedMaxVoteCostLimit,
+            29 => WouldExceedAccountDataTotalLimit,
+            30 => DuplicateInstruction,
+            31 => InsufficientFundsForRent,
+            32 => MaxLoadedAccountsDataSizeExceeded,
+            33 => InvalidLoadedAccountsDataSizeLimit,
+            34 => ResanitizationNeeded,
+            35 => ProgramExecutionTemporarilyRestricted,
+            36 => UnbalancedTransaction,
+            37 => ProgramCacheHitMaxLimit,
+            index => Unknown(index),
+        }
+    }
+}
+
+impl pb::TransactionError {
+    /// Decodes the kind of the error from its leading little-endian `u32` variant index.
+    pub fn kind(&self) -> TransactionErrorKind {
+        match self.err.get(..4) {
+            Some(bytes) => TransactionErrorKind::from_variant_index(u32::from_le_bytes(
+                bytes.try_into().expect("sliced 4 bytes"),
+            )),
+            None => TransactionErrorKind::Unknown(u32::MAX),
+        }
+    }
+}
+
+impl pb::ConfirmedTransaction {
+    /// Returns the kind of error the transaction failed with, [None] if the transaction
+    /// succeeded or has no meta.
+    pub fn classify_error(&self) -> Option<TransactionErrorKind> {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.err.as_ref())
+            .map(|err| err.kind())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountIndexError, TransactionErrorKind, TransactionValidationError};
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    fn trx(err: Option<Vec<u8>>) -> pb::ConfirmedTransaction {
+        pb::ConfirmedTransaction {
+            meta: Some(pb::TransactionStatusMeta {
+                err: err.map(|err| pb::TransactionError { err }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_classifies_transaction_errors() {
+        assert_eq!(None, trx(None).classify_error());
+        assert_eq!(None, pb::ConfirmedTransaction::default().classify_error());
+
+        assert_eq!(
+            Some(TransactionErrorKind::InsufficientFundsForFee),
+            trx(Some(vec![4, 0, 0, 0])).classify_error()
+        );
+        // InstructionError(1, Custom(6001))
+        assert_eq!(
+            Some(TransactionErrorKind::InstructionError),
+            trx(Some(vec![8, 0, 0, 0, 1, 25, 0, 0, 0, 0x71, 0x17, 0, 0])).classify_error()
+        );
+        assert_eq!(
+            Some(TransactionErrorKind::Unknown(200)),
+            trx(Some(vec![200, 0, 0, 0])).classify_error()
+        );
+        assert_eq!(
+            Some(TransactionErrorKind::Unknown(u32::MAX)),
+            trx(Some(vec![4])).classify_error()
+        );
+    }
+
+    #[test]
+    fn it_formats_errors() {
+        assert_eq!(
+            "account index 7 out of bounds, transaction has 3 accounts",
+            AccountIndexError { index: 7, len: 3 }.to_string()
+        );
+        assert_eq!(
+            "inner instructions index 4 out of range, transaction has 2 compiled instructions",
+            TransactionValidationError::InnerInstructionsIndexOutOfRange {
+                index: 4,
+                compiled_instruction_count: 2,
+            }
+            .to_string()
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! JSON rendering of transactions, available with the `json` feature, see
+//! [ConfirmedTransaction::to_json].
+
+use serde_json::{json, Value};
+
+use crate::{
+    base58,
+    pb::sf::solana::r#type::v1::{self as pb, ConfirmedTransaction},
+};
+
+impl ConfirmedTransaction {
+    /// Renders the transaction as JSON, approximating the shape of the RPC `getTransaction`
+    /// response with the `json` encoding (e.g. its `transaction`, `meta` and `version`
+    /// fields). Signatures, account keys, blockhash and instructions data are base58 encoded,
+    /// instructions reference their program id and accounts by index.
+    ///
+    /// This is a best-effort approximation meant for debugging and cross-checking against
+    /// RPC, not a byte for byte equivalent. Notably, the transaction error is rendered as
+    /// its raw bincode bytes (`{"raw": [...]}`) since it's not decoded, and the return data
+    /// is base58 encoded instead of base64.
+    pub fn to_json(&self) -> Value {
+        let message = self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref());
+
+        json!({
+            "transaction": self.transaction.as_ref().map(|trx| json!({
+                "signatures": trx.signatures.iter().map(base58::encode).collect::<Vec<_>>(),
+                "message": trx.message.as_ref().map(message_json),
+            })),
+            "meta": self.meta.as_ref().map(meta_json),
+            "version": match message {
+                Some(message) if message.versioned => json!(0),
+                _ => json!("legacy"),
+            },
+        })
+    }
+}
+
+fn message_json(message: &pb::Message) -> Value {
+    json!({
+        "header": message.header.as_ref().map(|header| json!({
+            "numRequiredSignatures": header.num_required_signatures,
+            "numReadonlySignedAccounts": header.num_readonly_signed_accounts,
+            "numReadonlyUnsignedAccounts": header.num_readonly_unsigned_accounts,
+        })),
+        "accountKeys": message.account_keys.iter().map(base58::encode).collect::<Vec<_>>(),
+        "recentBlockhash": base58::encode(&message.recent_blockhash),
+        "instructions": message
+            .instructions
+            .iter()
+            .map(|instruction| instruction_json(
+                instruction.program_id_index,
+                &instruction.accounts,
+                &instruction.data,
+                None,
+            ))
+            .collect::<Vec<_>>(),
+        "addressTableLookups": message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| json!({
+                "accountKey": base58::encode(&lookup.account_key),
+                "writableIndexes": lookup.writable_indexes,
+                "readonlyIndexes": lookup.readonly_indexes,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn meta_json(meta: &pb::TransactionStatusMeta) -> Value {
+    json!({
+        "err": meta.err.as_ref().map(|err| json!({ "raw": err.err })),
+        "fee": meta.fee,
+        "preBalances": meta.pre_balances,
+        "postBalances": meta.post_balances,
+        "innerInstructions": if meta.inner_instructions_none {
+            Value::Null
+        } else {
+            meta.inner_instructions
+                .iter()
+                .map(|inner_instructions| json!({
+                    "index": inner_instructions.index,
+                    "instructions": inner_instructions
+                        .instructions
+                        .iter()
+                        .map(|instruction| instruction_json(
+                            instruction.program_id_index,
+                            &instruction.accounts,
+                            &instruction.data,
+                            instruction.stack_height,
+                        ))
+                        .collect::<Vec<_>>(),
+                }))
+                .collect()
+        },
+        "logMessages": if meta.log_messages_none {
+            Value::Null
+        } else {
+            json!(meta.log_messages)
+        },
+        "preTokenBalances": meta.pre_token_balances.iter().map(token_balance_json).collect::<Vec<_>>(),
+        "postTokenBalances": meta.post_token_balances.iter().map(token_balance_json).collect::<Vec<_>>(),
+        "rewards": meta.rewards.iter().map(reward_json).collect::<Vec<_>>(),
+        "loadedAddresses": {
+            "writable": meta.loaded_writable_addresses.iter().map(base58::encode).collect::<Vec<_>>(),
+            "readonly": meta.loaded_readonly_addresses.iter().map(base58::encode).collect::<Vec<_>>(),
+        },
+        "returnData": meta.return_data.as_ref().map(|return_data| json!({
+            "programId": base58::encode(&return_data.program_id),
+            "data": [base58::encode(&return_data.data), "base58"],
+        })),
+        "computeUnitsConsumed": meta.compute_units_consumed,
+    })
+}
+
+fn instruction_json(
+    program_id_index: u32,
+    accounts: &[u8],
+    data: &[u8],
+    stack_height: Option<u32>,
+) -> Value {
+    json!({
+        "programIdIndex": program_id_index,
+        "accounts": accounts,
+        "data": base58::encode(data),
+        "stackHeight": stack_height,
+    })
+}
+
+fn token_balance_json(balance: &pb::TokenBalance) -> Value {
+    json!({
+        "accountIndex": balance.account_index,
+        "mint": balance.mint,
+        "owner": balance.owner,
+        "programId": balance.program_id,
+        "uiTokenAmount": balance.ui_token_amount.as_ref().map(|amount| json!({
+            "amount": amount.amount,
+            "decimals": amount.decimals,
+            "uiAmount": amount.ui_amount,
+            "uiAmountString": amount.ui_amount_string,
+        })),
+    })
+}
+
+fn reward_json(reward: &pb::Reward) -> Value {
+    let reward_type = match pb::RewardType::try_from(reward.reward_type) {
+        Ok(pb::RewardType::Fee) => json!("Fee"),
+        Ok(pb::RewardType::Rent) => json!("Rent"),
+        Ok(pb::RewardType::Staking) => json!("Staking"),
+        Ok(pb::RewardType::Voting) => json!("Voting"),
+        Ok(pb::RewardType::Unspecified) | Err(_) => Value::Null,
+    };
+
+    json!({
+        "pubkey": reward.pubkey,
+        "lamports": reward.lamports,
+        "postBalance": reward.post_balance,
+        "rewardType": reward_type,
+        "commission": reward.commission.parse::<u8>().ok(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    #[test]
+    fn it_renders_transaction_as_json() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![vec![1, 2, 3], vec![4, 5, 6]],
+                    recent_blockhash: vec![7, 8, 9],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0],
+                        data: vec![1, 2, 3],
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                fee: 5000,
+                pre_balances: vec![10_000, 1],
+                post_balances: vec![5_000, 1],
+                inner_instructions: vec![pb::InnerInstructions {
+                    index: 0,
+                    instructions: vec![pb::InnerInstruction {
+                        program_id_index: 1,
+                        accounts: vec![],
+                        data: vec![],
+                        stack_height: Some(2),
+                    }],
+                }],
+                log_messages: vec!["Program log: hello".to_string()],
+                compute_units_consumed: Some(150),
+                ..Default::default()
+            }),
+        };
+
+        let value = trx.to_json();
+
+        assert_eq!(json!(["Ldp"]), value["transaction"]["signatures"]);
+        assert_eq!(
+            json!(["Ldp", "2MJu"]),
+            value["transaction"]["message"]["accountKeys"]
+        );
+        assert_eq!(
+            json!([{ "programIdIndex": 1, "accounts": [0], "data": "Ldp", "stackHeight": null }]),
+            value["transaction"]["message"]["instructions"]
+        );
+        assert_eq!(
+            json!(1),
+            value["transaction"]["message"]["header"]["numReadonlyUnsignedAccounts"]
+        );
+        assert_eq!(json!(null), value["meta"]["err"]);
+        assert_eq!(json!(5000), value["meta"]["fee"]);
+        assert_eq!(
+            json!(2),
+            value["meta"]["innerInstructions"][0]["instructions"][0]["stackHeight"]
+        );
+        assert_eq!(json!(["Program log: hello"]), value["meta"]["logMessages"]);
+        assert_eq!(json!(150), value["meta"]["computeUnitsConsumed"]);
+        assert_eq!(json!("legacy"), value["version"]);
+    }
+}
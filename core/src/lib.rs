@@ -1,12 +1,49 @@
-use std::ops::Deref;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
 
-use address::Address;
-use pb::sf::solana::r#type::v1::{CompiledInstruction, InnerInstruction, Transaction};
+use address::{AccountMeta, AccountSource, Address, AddressOwned};
+use error::AccountIndexError;
+use pb::sf::solana::r#type::v1::{
+    CompiledInstruction, InnerInstruction, Message, MessageHeader, TokenBalance, Transaction,
+};
 
 use crate::pb::sf::solana::r#type::v1::ConfirmedTransaction;
 
+/// Emits a `tracing::debug!` event, when the `tracing` feature is enabled, reporting
+/// that a built-in decoder matched an instruction's program but failed to decode its
+/// data. It expands to nothing when the feature is disabled.
+macro_rules! trace_decode_failure {
+    ($view:expr, $decoder:expr) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!(
+            program_id = %$view.program_id(),
+            data_len = $view.data().len(),
+            "{} decoder failed to decode instruction data",
+            $decoder
+        );
+    };
+}
+
 pub mod address;
+#[cfg(feature = "anchor")]
+pub mod anchor;
+pub mod balance;
+pub mod compute_budget;
+pub mod cursor;
+pub mod decode;
+pub mod discriminator;
+pub mod error;
+#[cfg(feature = "json")]
+mod json;
 pub mod pb;
+pub mod programs;
+pub mod resolved;
+#[cfg(feature = "spl")]
+pub mod spl;
+#[cfg(feature = "system")]
+pub mod system;
 
 /// Helpers to deal with block sources.
 pub mod block_view;
@@ -32,6 +69,33 @@ pub mod base58 {
     pub fn decode<T: AsRef<str>>(data: T) -> Result<Vec<u8>, bs58::decode::Error> {
         bs58::decode(data.as_ref()).into_vec()
     }
+
+    /// Returns the maximum length of the base58 encoding of `byte_len` bytes, computed as
+    /// `ceil(byte_len * log(256) / log(58))`, without encoding anything. Use it to pre-size
+    /// buffers, e.g. 32 bytes addresses encode to at most 44 characters and 64 bytes
+    /// signatures to at most 88 characters.
+    ///
+    /// The actual encoding can be shorter since its length depends on the encoded value, not
+    /// only on its length.
+    pub fn encoded_len(byte_len: usize) -> usize {
+        // log(256) / log(58) = 1.365658237..., rounded up to stay an upper bound
+        const RATIO_NUMERATOR: u128 = 1_365_658_238;
+        const RATIO_DENOMINATOR: u128 = 1_000_000_000;
+
+        ((byte_len as u128 * RATIO_NUMERATOR + RATIO_DENOMINATOR - 1) / RATIO_DENOMINATOR) as usize
+    }
+
+    /// Decodes a list of base58 values separated by whitespace and/or commas, like
+    /// `"addr1, addr2 addr3"`. Empty entries are ignored. This is useful to parse
+    /// Substreams params carrying a list of addresses.
+    ///
+    /// On failure, the offending token is returned alongside the decoding error.
+    pub fn decode_list(s: &str) -> Result<Vec<Vec<u8>>, (String, bs58::decode::Error)> {
+        s.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| decode(token).map_err(|e| (token.to_string(), e)))
+            .collect()
+    }
 }
 
 /// Instruction trait to be implemented by all instructions. The trait enables you to work on
@@ -46,6 +110,29 @@ pub trait Instruction {
     fn accounts(&self) -> &Vec<u8>;
     fn data(&self) -> &Vec<u8>;
     fn stack_height(&self) -> Option<u32>;
+
+    /// Returns the first 8 bytes of the instruction's data, the Anchor-style instruction
+    /// selector (sighash), or [None] if the data is shorter than 8 bytes.
+    fn anchor_discriminator(&self) -> Option<[u8; 8]> {
+        self.data().get(..8).and_then(|bytes| bytes.try_into().ok())
+    }
+
+    /// Returns the first byte of the instruction's data, the instruction variant of SPL
+    /// Token and most native programs, or [None] if the data is empty.
+    fn variant_tag(&self) -> Option<u8> {
+        self.data().first().copied()
+    }
+}
+
+/// Resolves the program id of any [Instruction] implementor, like a stored
+/// [CompiledInstruction], against the transaction it belongs to. See
+/// [ConfirmedTransaction::account_at] for the resolution rules, the function panics if the
+/// program id index is out of bounds.
+pub fn resolve_program_id<'a>(
+    instruction: &impl Instruction,
+    trx: &'a ConfirmedTransaction,
+) -> Address<'a> {
+    trx.account_at(instruction.program_id_index() as u8)
 }
 
 impl<'a> Instruction for Box<dyn Instruction + 'a> {
@@ -173,6 +260,38 @@ impl ConfirmedTransaction {
         self.transaction.as_ref().unwrap().hash()
     }
 
+    /// Returns the transaction id (first signature) as a fixed size byte array, see
+    /// [Transaction::signature_array] for details. Use [Self::id] to get it as a base58
+    /// string.
+    pub fn id_bytes(&self) -> Option<[u8; 64]> {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.signature_array())
+    }
+
+    /// Returns a 64-bit fingerprint of the transaction, computed by hashing its first
+    /// signature with 64-bit FNV-1a. The hasher is fixed so the fingerprint is stable across
+    /// runs, platforms and versions of this library, making it suitable as a dedup or
+    /// bucketing key. It's **not** a cryptographic hash, collisions are possible and must be
+    /// tolerated by the caller.
+    ///
+    /// A transaction without signature gets the FNV-1a offset basis `0xcbf29ce484222325`.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let signature = self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.signatures.first())
+            .map(|signature| signature.as_slice())
+            .unwrap_or(&[]);
+
+        signature.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+
     /// Returns the resolved accounts for the transaction. The resolved accounts are the
     /// accounts that are used in the transaction message and the accounts that are loaded
     /// by the transaction's meta.
@@ -185,50 +304,504 @@ impl ConfirmedTransaction {
     /// # let trx = substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction::default();
     /// let accounts: Vec<_> = trx.resolved_accounts().iter().map(base58::encode).collect();
     /// ```
+    ///
+    /// If the transaction has no meta, there are no loaded addresses and only the message's
+    /// account keys are returned.
     pub fn resolved_accounts(&self) -> Vec<&Vec<u8>> {
-        let meta = self.meta.as_ref().unwrap();
         let message = self.transaction.as_ref().unwrap().message.as_ref().unwrap();
 
         let mut accounts = vec![];
         accounts.extend(message.account_keys.iter());
-        accounts.extend(meta.loaded_writable_addresses.iter());
-        accounts.extend(meta.loaded_readonly_addresses.iter());
+        if let Some(meta) = self.meta.as_ref() {
+            accounts.extend(meta.loaded_writable_addresses.iter());
+            accounts.extend(meta.loaded_readonly_addresses.iter());
+        }
 
         accounts
     }
 
+    /// Returns the resolved accounts of the transaction like [Self::resolved_accounts], in
+    /// the same order, each wrapped in an [Address].
+    ///
+    /// ```no_run
+    /// # use substreams_solana_core::address::Address;
+    /// # let trx = substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction::default();
+    /// let accounts: Vec<_> = trx.resolved_accounts_as_addresses().iter().map(Address::to_string).collect();
+    /// ```
+    pub fn resolved_accounts_as_addresses(&self) -> Vec<Address<'_>> {
+        self.resolved_accounts().into_iter().map(Address).collect()
+    }
+
     /// Returns the account at the given index. The index is the index of the account in the
     /// transaction message's account keys/meta loaded writable/readonly addresses. If the
-    /// index is out of bounds, the method panics.
-    pub fn account_at<'a>(&'a self, index: u8) -> Address<'a> {
+    /// index is out of bounds, the method panics, use [Self::try_account_at] to handle it.
+    ///
+    /// If the transaction has no meta, only the message's account keys can be resolved.
+    pub fn account_at(&self, index: u8) -> Address<'_> {
+        self.try_account_at(index)
+            .unwrap_or_else(|| panic!("Account index {} out of bounds", index))
+    }
+
+    /// Returns the account at the given index like [Self::account_at] but returns [None]
+    /// instead of panicking when the index is out of bounds, or when the transaction has
+    /// no message. Prefer it when processing untrusted account indices, e.g. read from
+    /// instruction data, to skip the instruction instead of aborting the module.
+    pub fn try_account_at(&self, index: u8) -> Option<Address<'_>> {
         let mut i: usize = index as usize;
 
-        let account_keys = &self
+        let account_keys = &self.transaction.as_ref()?.message.as_ref()?.account_keys;
+        if i < account_keys.len() {
+            return Some(Address(&account_keys[i]));
+        }
+
+        let meta = self.meta.as_ref()?;
+        i -= account_keys.len();
+        if i < meta.loaded_writable_addresses.len() {
+            return Some(Address(&meta.loaded_writable_addresses[i]));
+        }
+
+        i -= meta.loaded_writable_addresses.len();
+        meta.loaded_readonly_addresses.get(i).map(Address)
+    }
+
+    /// Returns the account at the given index like [Self::account_at] but returns an
+    /// [AccountIndexError] carrying the requested index and the number of resolved accounts
+    /// instead of panicking when the index is out of bounds. A transaction without message
+    /// has no resolved accounts.
+    pub fn account_at_result(&self, index: u8) -> Result<Address<'_>, AccountIndexError> {
+        self.try_account_at(index).ok_or_else(|| AccountIndexError {
+            index,
+            len: self.resolved_accounts_len(),
+        })
+    }
+
+    /// Returns the number of resolved accounts, see [Self::resolved_accounts], without
+    /// panicking nor allocating, `0` if the transaction has no message.
+    fn resolved_accounts_len(&self) -> usize {
+        let account_keys_len = match self
+            .transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+        {
+            Some(message) => message.account_keys.len(),
+            None => return 0,
+        };
+
+        account_keys_len
+            + self.meta.as_ref().map_or(0, |meta| {
+                meta.loaded_writable_addresses.len() + meta.loaded_readonly_addresses.len()
+            })
+    }
+
+    /// Iterates over the indices of the resolved accounts, see [Self::resolved_accounts],
+    /// stopping at the last one an `u8` account index can address.
+    fn resolved_account_indices(&self) -> impl Iterator<Item = u8> {
+        (0..self.resolved_accounts_len()).map_while(|i| u8::try_from(i).ok())
+    }
+
+    /// Returns `true` if the account at the given index signed the transaction. The index
+    /// is the same as the one used by [Self::account_at]. Only the first
+    /// `header.num_required_signatures` account keys of the message are signers, accounts
+    /// loaded through address lookup tables never are.
+    pub fn is_signer(&self, index: u8) -> bool {
+        match self.message_header() {
+            Some(header) => (index as u32) < header.num_required_signatures,
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the account at the given index is writable by the transaction. The
+    /// index is the same as the one used by [Self::account_at].
+    ///
+    /// For the message's account keys, the privileges are derived from the message header:
+    /// signers come first, the last `header.num_readonly_signed_accounts` of them being
+    /// read-only, followed by non-signers, the last `header.num_readonly_unsigned_accounts`
+    /// of them being read-only. Accounts loaded through address lookup tables are writable
+    /// if they are part of the meta's loaded writable addresses.
+    pub fn is_writable(&self, index: u8) -> bool {
+        let header = match self.message_header() {
+            Some(header) => header,
+            None => return false,
+        };
+
+        let i = index as usize;
+        let account_keys_len = self
             .transaction
             .as_ref()
             .unwrap()
             .message
             .as_ref()
             .unwrap()
-            .account_keys;
+            .account_keys
+            .len();
+        if i < account_keys_len {
+            let signers = header.num_required_signatures as usize;
+            if i < signers {
+                return i < signers.saturating_sub(header.num_readonly_signed_accounts as usize);
+            }
 
-        if i < account_keys.len() {
-            return Address(&account_keys[i]);
+            return i < account_keys_len
+                .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+        }
+
+        match self.meta.as_ref() {
+            Some(meta) => i - account_keys_len < meta.loaded_writable_addresses.len(),
+            None => false,
         }
+    }
 
-        let meta = self.meta.as_ref().unwrap();
+    /// Returns the segment of the resolved accounts the account at the given index comes
+    /// from, the index being the same as the one used by [Self::account_at]: the message's
+    /// static account keys come first, followed by the meta's loaded writable addresses and
+    /// then the meta's loaded readonly addresses.
+    ///
+    /// Returns [None] if the index is out of bounds or the transaction has no message.
+    pub fn account_source(&self, index: u8) -> Option<AccountSource> {
+        let message = self.transaction.as_ref()?.message.as_ref()?;
+
+        let mut i = index as usize;
+        if i < message.account_keys.len() {
+            return Some(AccountSource::Static);
+        }
+        i -= message.account_keys.len();
 
-        i = i - account_keys.len();
+        let meta = self.meta.as_ref()?;
         if i < meta.loaded_writable_addresses.len() {
-            return Address(&meta.loaded_writable_addresses[i]);
+            return Some(AccountSource::LoadedWritable);
         }
+        i -= meta.loaded_writable_addresses.len();
 
-        i = i - meta.loaded_writable_addresses.len();
         if i < meta.loaded_readonly_addresses.len() {
-            return Address(&meta.loaded_readonly_addresses[i]);
+            return Some(AccountSource::LoadedReadonly);
+        }
+
+        None
+    }
+
+    /// Returns `true` if the account at the given index was loaded through an address lookup
+    /// table, e.g. it's one of the meta's loaded writable or readonly addresses rather than
+    /// one of the message's static account keys. See [Self::account_source] for details.
+    pub fn is_loaded_address(&self, index: u8) -> bool {
+        matches!(
+            self.account_source(index),
+            Some(AccountSource::LoadedWritable) | Some(AccountSource::LoadedReadonly)
+        )
+    }
+
+    /// Returns the [AccountMeta] of the account at the given index, that is the resolved
+    /// address along with its signer and writable privileges. If the index is out of bounds,
+    /// the method panics.
+    pub fn account_meta_at(&self, index: u8) -> AccountMeta<'_> {
+        AccountMeta {
+            address: self.account_at(index),
+            is_signer: self.is_signer(index),
+            is_writable: self.is_writable(index),
+        }
+    }
+
+    /// Returns the [AccountMeta] of all resolved accounts of the transaction, in the same
+    /// order as [Self::resolved_accounts]. Accounts past index 255, which no instruction
+    /// can reference, are not returned.
+    pub fn account_metas(&self) -> Vec<AccountMeta<'_>> {
+        self.resolved_account_indices()
+            .map(|i| self.account_meta_at(i))
+            .collect()
+    }
+
+    /// Iterates over the accounts that signed the transaction, the first one being the fee
+    /// payer. Those are the first `header.num_required_signatures` account keys of the
+    /// message, see [Self::is_signer].
+    pub fn signers(&self) -> impl Iterator<Item = Address<'_>> + '_ {
+        let signer_count = self
+            .message_header()
+            .map(|header| header.num_required_signatures as usize)
+            .unwrap_or(0);
+
+        self.transaction
+            .iter()
+            .flat_map(|trx| trx.message.iter())
+            .flat_map(move |message| message.account_keys.iter().take(signer_count))
+            .map(Address)
+    }
+
+    /// Returns the fee payer of the transaction, the first account key of the message which
+    /// is always a writable signer. Returns [None] if the transaction has no message or no
+    /// account keys.
+    pub fn fee_payer(&self) -> Option<Address<'_>> {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .and_then(|message| message.account_keys.first())
+            .map(Address)
+    }
+
+    /// Returns the fee paid by the transaction in lamports, `0` if the transaction has no
+    /// meta.
+    pub fn fee(&self) -> u64 {
+        self.meta.as_ref().map_or(0, |meta| meta.fee)
+    }
+
+    /// Returns the compute units consumed by the transaction, [None] if the transaction has
+    /// no meta or if the meta doesn't record it (blocks before Solana v1.10).
+    pub fn compute_units_consumed(&self) -> Option<u64> {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.compute_units_consumed)
+    }
+
+    /// Returns the size in bytes of the transaction once serialized in Solana's wire format,
+    /// e.g. the signatures followed by the serialized message, see [Message::serialized_size].
+    ///
+    /// The size is computed from the transaction's content, it's not stored in the block. A
+    /// missing transaction has a size of `0`.
+    pub fn serialized_size(&self) -> usize {
+        self.transaction
+            .as_ref()
+            .map(|trx| trx.serialized_size())
+            .unwrap_or(0)
+    }
+
+    /// Returns `true` if the transaction carries exactly the number of signatures required
+    /// by its message header (`header.num_required_signatures`). A mismatch, or a missing
+    /// transaction, message or header, denotes synthetic or corrupted data.
+    pub fn is_signature_count_valid(&self) -> bool {
+        match (self.transaction.as_ref(), self.message_header()) {
+            (Some(trx), Some(header)) => {
+                trx.signatures.len() == header.num_required_signatures as usize
+            }
+            _ => false,
+        }
+    }
+
+    /// Iterates over the resolved accounts that are writable by the transaction, see
+    /// [Self::is_writable] for the rules used. Only those accounts can have their state
+    /// changed by the transaction.
+    ///
+    /// The iterator is empty if the transaction has no message.
+    pub fn writable_accounts(&self) -> impl Iterator<Item = Address<'_>> + '_ {
+        self.resolved_account_indices()
+            .filter(move |i| self.is_writable(*i))
+            .filter_map(move |i| self.try_account_at(i))
+    }
+
+    /// Iterates over the resolved accounts that are read-only for the transaction, see
+    /// [Self::is_writable] for the rules used.
+    ///
+    /// The iterator is empty if the transaction has no message.
+    pub fn readonly_accounts(&self) -> impl Iterator<Item = Address<'_>> + '_ {
+        self.resolved_account_indices()
+            .filter(move |i| !self.is_writable(*i))
+            .filter_map(move |i| self.try_account_at(i))
+    }
+
+    /// Iterates over the accounts that both signed the transaction and are writable by it,
+    /// typically the fee payer and mutable authorities. See [Self::is_signer] and
+    /// [Self::is_writable] for the rules used.
+    pub fn writable_signers(&self) -> impl Iterator<Item = Address<'_>> + '_ {
+        self.signers()
+            .enumerate()
+            .filter(move |(i, _)| self.is_writable(*i as u8))
+            .map(|(_, account)| account)
+    }
+
+    /// Returns the accounts the transaction write-locks, in the canonical order the runtime
+    /// acquires them: writable signers first, then writable non-signers of the message's
+    /// account keys, then the writable accounts loaded through address lookup tables. See
+    /// [Self::is_writable] for the rules used.
+    ///
+    /// The runtime's demotion of reserved accounts (sysvars, builtin programs) to read-only
+    /// is not applied, the header's privileges are taken as is. The list is empty if the
+    /// transaction has no message.
+    pub fn write_locks(&self) -> Vec<Address<'_>> {
+        self.writable_accounts().collect()
+    }
+
+    /// Iterates over the accounts loaded through address lookup tables, excluding the
+    /// message's static account keys, along with a flag telling whether the account is
+    /// writable. Writable loaded accounts come first, in resolved accounts order.
+    ///
+    /// The iterator is empty if the transaction has no meta.
+    pub fn lookup_loaded_accounts(&self) -> impl Iterator<Item = (Address<'_>, bool)> + '_ {
+        self.meta.iter().flat_map(|meta| {
+            meta.loaded_writable_addresses
+                .iter()
+                .map(|account| (Address(account), true))
+                .chain(
+                    meta.loaded_readonly_addresses
+                        .iter()
+                        .map(|account| (Address(account), false)),
+                )
+        })
+    }
+
+    /// Returns the distinct mints of the transaction's pre and post token balances, e.g.
+    /// the tokens whose balances the transaction involved, without decoding any
+    /// instruction. Mints that are not valid base58 are ignored.
+    ///
+    /// Returns an empty set if the transaction has no meta.
+    pub fn token_mints(&self) -> HashSet<AddressOwned> {
+        self.meta
+            .iter()
+            .flat_map(|meta| {
+                meta.pre_token_balances
+                    .iter()
+                    .chain(meta.post_token_balances.iter())
+            })
+            .filter_map(|balance| base58::decode(&balance.mint).ok())
+            .map(AddressOwned)
+            .collect()
+    }
+
+    /// Returns the net token balance changes of the transaction keyed by `(owner, mint)`, in
+    /// raw amount (e.g. not adjusted by the mint's decimals).
+    ///
+    /// The pre and post token balances are joined by account index, an account present only
+    /// in the pre (resp. post) token balances being considered as having a post (resp. pre)
+    /// amount of zero. The pre amount is debited from the pre owner and the post amount
+    /// credited to the post owner, so the balance of an account whose owner changed moves
+    /// from one owner to the other. The per account deltas are then summed per owner and
+    /// mint, entries whose net change is zero are kept. Balances whose owner, mint or amount
+    /// can't be decoded are ignored.
+    ///
+    /// Returns an empty map if the transaction has no meta.
+    pub fn token_deltas_by_owner(&self) -> HashMap<(AddressOwned, AddressOwned), i128> {
+        let mut deltas = HashMap::new();
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return deltas,
+        };
+
+        let decode = |balance: &TokenBalance| -> Option<(AddressOwned, AddressOwned, i128)> {
+            let owner = base58::decode(&balance.owner).ok()?;
+            let mint = base58::decode(&balance.mint).ok()?;
+            let amount = balance.ui_token_amount.as_ref()?.amount.parse().ok()?;
+
+            Some((AddressOwned(owner), AddressOwned(mint), amount))
+        };
+
+        let pre = meta
+            .pre_token_balances
+            .iter()
+            .map(|balance| (balance.account_index, balance))
+            .collect::<HashMap<_, _>>();
+
+        for balance in meta.post_token_balances.iter() {
+            let (owner, mint, amount) = match decode(balance) {
+                Some(decoded) => decoded,
+                None => continue,
+            };
+            *deltas.entry((owner, mint)).or_insert(0) += amount;
+
+            // The pre amount is debited from the pre owner, which differs from the post one
+            // when the account's owner changed, see `owner_changes`
+            if let Some((owner, mint, amount)) =
+                pre.get(&balance.account_index).and_then(|b| decode(b))
+            {
+                *deltas.entry((owner, mint)).or_insert(0) -= amount;
+            }
+        }
+
+        let post_indices = meta
+            .post_token_balances
+            .iter()
+            .map(|balance| balance.account_index)
+            .collect::<HashSet<_>>();
+
+        for balance in meta.pre_token_balances.iter() {
+            if post_indices.contains(&balance.account_index) {
+                continue;
+            }
+
+            if let Some((owner, mint, amount)) = decode(balance) {
+                *deltas.entry((owner, mint)).or_insert(0) -= amount;
+            }
         }
 
-        panic!("Account index {} out of bounds", index);
+        deltas
+    }
+
+    /// Returns the token accounts whose owner changed during the transaction, typically
+    /// through a `SetAuthority` instruction, as `(token_account, pre_owner, post_owner)`
+    /// tuples in post token balances order.
+    ///
+    /// The pre and post token balances are joined by account index, accounts present in
+    /// only one of them are ignored, as are balances whose owner can't be decoded or whose
+    /// account index can't be resolved.
+    ///
+    /// Returns an empty list if the transaction has no meta.
+    pub fn owner_changes(&self) -> Vec<(AddressOwned, AddressOwned, AddressOwned)> {
+        let meta = match self.meta.as_ref() {
+            Some(meta) => meta,
+            None => return vec![],
+        };
+
+        let pre_owners = meta
+            .pre_token_balances
+            .iter()
+            .map(|balance| (balance.account_index, &balance.owner))
+            .collect::<HashMap<_, _>>();
+
+        meta.post_token_balances
+            .iter()
+            .filter_map(|balance| {
+                let pre_owner = *pre_owners.get(&balance.account_index)?;
+                if *pre_owner == balance.owner {
+                    return None;
+                }
+
+                let account = self.try_account_at(balance.account_index.try_into().ok()?)?;
+                Some((
+                    account.to_owned(),
+                    AddressOwned(base58::decode(pre_owner).ok()?),
+                    AddressOwned(base58::decode(&balance.owner).ok()?),
+                ))
+            })
+            .collect()
+    }
+
+    fn message_header(&self) -> Option<&MessageHeader> {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .and_then(|message| message.header.as_ref())
+    }
+
+    /// Returns the number of address lookup tables referenced by the transaction's message,
+    /// without resolving anything. Legacy transactions and transactions without a message
+    /// return `0`.
+    pub fn lookup_table_count(&self) -> usize {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .map(|message| message.address_table_lookups.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns the recent blockhash of the transaction's message, the blockhash the
+    /// transaction was signed against, or the nonce value for durable nonce transactions.
+    /// Returns [None] if the transaction has no message.
+    ///
+    /// Use [Address::to_string] to get its base58 representation.
+    pub fn recent_blockhash(&self) -> Option<Address<'_>> {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .map(|message| Address(&message.recent_blockhash))
+    }
+
+    /// Returns the version of the transaction's message format, see [Message::version].
+    /// Returns [TransactionVersion::Legacy] if the transaction has no message.
+    pub fn version(&self) -> TransactionVersion {
+        self.transaction
+            .as_ref()
+            .and_then(|trx| trx.message.as_ref())
+            .map_or(TransactionVersion::Legacy, Message::version)
+    }
+
+    /// Returns `true` if the transaction uses a versioned message format, e.g. its
+    /// [Self::version] is not [TransactionVersion::Legacy].
+    pub fn is_versioned(&self) -> bool {
+        self.version() != TransactionVersion::Legacy
     }
 }
 
@@ -244,11 +817,103 @@ impl Transaction {
     pub fn hash(&self) -> &[u8] {
         &self.signatures[0]
     }
+
+    /// Returns the transaction's first signature, which is the transaction id, as a fixed
+    /// size byte array. Returns [None] if the transaction has no signature or if it's not
+    /// 64 bytes long.
+    pub fn signature_array(&self) -> Option<[u8; 64]> {
+        self.signatures
+            .first()
+            .and_then(|signature| <[u8; 64]>::try_from(signature.as_slice()).ok())
+    }
+
+    /// Returns the size in bytes of the transaction once serialized in Solana's wire format,
+    /// e.g. the compact-u16 encoded signatures count, the signatures and the serialized
+    /// message, see [Message::serialized_size]. It's computed and not stored in the block.
+    pub fn serialized_size(&self) -> usize {
+        compact_u16_len(self.signatures.len())
+            + self.signatures.iter().map(Vec::len).sum::<usize>()
+            + self
+                .message
+                .as_ref()
+                .map(Message::serialized_size)
+                .unwrap_or(0)
+    }
+}
+
+impl Message {
+    /// Returns the size in bytes of the message once serialized in Solana's wire format,
+    /// with the version prefix for versioned messages and the address table lookups. It's
+    /// computed and not stored in the block.
+    pub fn serialized_size(&self) -> usize {
+        let mut size = if self.versioned { 1 } else { 0 };
+
+        // Header is always 3 bytes
+        size += 3;
+        size += compact_u16_len(self.account_keys.len());
+        size += self.account_keys.iter().map(Vec::len).sum::<usize>();
+        size += self.recent_blockhash.len();
+
+        size += compact_u16_len(self.instructions.len());
+        for instruction in self.instructions.iter() {
+            size += 1;
+            size += compact_u16_len(instruction.accounts.len()) + instruction.accounts.len();
+            size += compact_u16_len(instruction.data.len()) + instruction.data.len();
+        }
+
+        if self.versioned {
+            size += compact_u16_len(self.address_table_lookups.len());
+            for lookup in self.address_table_lookups.iter() {
+                size += lookup.account_key.len();
+                size +=
+                    compact_u16_len(lookup.writable_indexes.len()) + lookup.writable_indexes.len();
+                size +=
+                    compact_u16_len(lookup.readonly_indexes.len()) + lookup.readonly_indexes.len();
+            }
+        }
+
+        size
+    }
+
+    /// Returns the version of the message format, [TransactionVersion::V0] for versioned
+    /// messages and [TransactionVersion::Legacy] otherwise.
+    pub fn version(&self) -> TransactionVersion {
+        if self.versioned {
+            TransactionVersion::V0
+        } else {
+            TransactionVersion::Legacy
+        }
+    }
+}
+
+/// The version of a transaction's message format, see [Message::version].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionVersion {
+    /// The original message format, without address table lookups.
+    Legacy,
+    /// The version `0` message format, supporting address table lookups.
+    V0,
+}
+
+/// Returns the number of bytes used by Solana's compact-u16 encoding of `value`, 7 bits
+/// being encoded per byte.
+fn compact_u16_len(value: usize) -> usize {
+    match value {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::pb::sf::solana::r#type::v1 as pb;
+    use crate::{
+        address::{AccountSource, Address, AddressOwned},
+        base58,
+        error::AccountIndexError,
+        pb::sf::solana::r#type::v1 as pb,
+        Instruction, TransactionVersion,
+    };
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -293,6 +958,742 @@ mod tests {
         assert_eq!(bytes("a6"), trx.account_at(6));
     }
 
+    #[test]
+    fn it_returns_instruction_anchor_discriminator() {
+        let compiled = pb::CompiledInstruction {
+            data: vec![175, 175, 109, 31, 13, 152, 155, 237, 1, 2, 3, 4],
+            ..Default::default()
+        };
+        let inner = pb::InnerInstruction {
+            data: vec![1, 2, 3, 4],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some([175, 175, 109, 31, 13, 152, 155, 237]),
+            compiled.anchor_discriminator()
+        );
+        assert_eq!(None, inner.anchor_discriminator());
+
+        let boxed: Box<dyn Instruction> = Box::new(compiled.clone());
+        assert_eq!(
+            compiled.anchor_discriminator(),
+            boxed.anchor_discriminator()
+        );
+    }
+
+    #[test]
+    fn it_returns_instruction_variant_tag() {
+        let instruction = |data: Vec<u8>| pb::CompiledInstruction {
+            data,
+            ..Default::default()
+        };
+
+        assert_eq!(None, instruction(vec![]).variant_tag());
+        assert_eq!(Some(3), instruction(vec![3]).variant_tag());
+        assert_eq!(Some(3), instruction(vec![3, 1, 2]).variant_tag());
+    }
+
+    #[test]
+    fn it_decodes_base58_list() {
+        assert_eq!(
+            Ok(vec![
+                vec![0x02, 0x8c, 0x6d],
+                vec![0x01, 0x3e, 0x61],
+                vec![0x02, 0x8c, 0x6d]
+            ]),
+            crate::base58::decode_list(" reg,REG\n\treg , ")
+        );
+
+        assert_eq!(Ok(Vec::<Vec<u8>>::new()), crate::base58::decode_list(" , "));
+
+        let (token, _) = crate::base58::decode_list("reg 0OIl REG").unwrap_err();
+        assert_eq!("0OIl", token);
+    }
+
+    #[test]
+    fn it_computes_base58_encoded_len() {
+        assert_eq!(0, base58::encoded_len(0));
+        assert_eq!(2, base58::encoded_len(1));
+        assert_eq!(44, base58::encoded_len(32));
+        assert_eq!(88, base58::encoded_len(64));
+
+        for len in [1, 2, 31, 32, 64] {
+            assert_eq!(
+                base58::encoded_len(len),
+                base58::encode(vec![0xff; len]).len()
+            );
+        }
+        assert!(base58::encode([6; 32]).len() <= base58::encoded_len(32));
+    }
+
+    #[test]
+    fn it_counts_lookup_tables() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![bytes("a0"), bytes("a1")],
+                    versioned: true,
+                    address_table_lookups: vec![
+                        pb::MessageAddressTableLookup {
+                            account_key: bytes("b0"),
+                            writable_indexes: vec![0],
+                            readonly_indexes: vec![1, 2],
+                        },
+                        pb::MessageAddressTableLookup {
+                            account_key: bytes("b1"),
+                            writable_indexes: vec![],
+                            readonly_indexes: vec![0],
+                        },
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        assert_eq!(2, trx.lookup_table_count());
+        assert_eq!(0, pb::ConfirmedTransaction::default().lookup_table_count());
+    }
+
+    #[test]
+    fn it_returns_recent_blockhash() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    recent_blockhash: vec![4, 5, 6],
+                    ..Default::default()
+                }),
+            }),
+            meta: None,
+        };
+
+        let blockhash = trx.recent_blockhash().unwrap();
+        assert_eq!(vec![4, 5, 6], blockhash);
+        assert_eq!("2MJu", blockhash.to_string());
+
+        assert_eq!(None, pb::ConfirmedTransaction::default().recent_blockhash());
+    }
+
+    #[test]
+    fn it_returns_transaction_version() {
+        let trx = |versioned: bool| pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    versioned,
+                    ..Default::default()
+                }),
+            }),
+            meta: None,
+        };
+
+        assert_eq!(TransactionVersion::Legacy, trx(false).version());
+        assert!(!trx(false).is_versioned());
+
+        assert_eq!(TransactionVersion::V0, trx(true).version());
+        assert!(trx(true).is_versioned());
+
+        assert_eq!(
+            TransactionVersion::Legacy,
+            pb::ConfirmedTransaction::default().version()
+        );
+    }
+
+    #[test]
+    fn it_resolves_account_privileges() {
+        let trx = privileged_trx();
+
+        let privileges = (0..6)
+            .map(|i| (trx.is_signer(i), trx.is_writable(i)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (true, true),
+                (true, false),
+                (false, true),
+                (false, false),
+                (false, true),
+                (false, false)
+            ],
+            privileges
+        );
+
+        let metas = trx.account_metas();
+        assert_eq!(6, metas.len());
+        assert_eq!(bytes("a4"), metas[4].address);
+        assert_eq!(false, metas[4].is_signer);
+        assert_eq!(true, metas[4].is_writable);
+    }
+
+    #[test]
+    fn it_stops_account_iteration_at_the_last_addressable_index() {
+        let mut trx = privileged_trx();
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.account_keys = (0..300u16).map(|i| i.to_be_bytes().to_vec()).collect();
+
+        let metas = trx.account_metas();
+        assert_eq!(256, metas.len());
+        assert_eq!(255u16.to_be_bytes().to_vec(), *metas[255].address.0);
+        assert_eq!(
+            256,
+            trx.writable_accounts().count() + trx.readonly_accounts().count()
+        );
+    }
+
+    #[test]
+    fn it_resolves_instruction_account_metas_in_instruction_order() {
+        let mut trx = privileged_trx();
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.instructions = vec![pb::CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![3, 0, 4, 2, 0, 1],
+            data: vec![],
+        }];
+
+        let view = trx.compiled_instructions().next().unwrap();
+        let metas = view
+            .account_metas()
+            .into_iter()
+            .map(|meta| (hex::encode(meta.address), meta.is_signer, meta.is_writable))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("a3".to_string(), false, false),
+                ("a0".to_string(), true, true),
+                ("a4".to_string(), false, true),
+                ("a2".to_string(), false, true),
+                ("a0".to_string(), true, true),
+                ("a1".to_string(), true, false),
+            ],
+            metas
+        );
+    }
+
+    #[test]
+    fn it_resolves_accounts_without_meta() {
+        let mut trx = privileged_trx();
+        trx.meta = None;
+
+        assert_eq!(
+            vec![&bytes("a0"), &bytes("a1"), &bytes("a2"), &bytes("a3")],
+            trx.resolved_accounts()
+        );
+        assert_eq!(bytes("a3"), trx.account_at(3));
+    }
+
+    #[test]
+    fn it_resolves_accounts_as_addresses() {
+        let trx = privileged_trx();
+
+        assert_eq!(
+            trx.resolved_accounts(),
+            trx.resolved_accounts_as_addresses()
+                .into_iter()
+                .map(|address| address.0)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["a0", "a1", "a2", "a3", "a4", "a5"],
+            trx.resolved_accounts_as_addresses()
+                .iter()
+                .map(::hex::encode)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_returns_fee_and_compute_units_consumed() {
+        let mut trx = privileged_trx();
+        let meta = trx.meta.as_mut().unwrap();
+        meta.fee = 5_000;
+        meta.compute_units_consumed = Some(150);
+
+        assert_eq!(5_000, trx.fee());
+        assert_eq!(Some(150), trx.compute_units_consumed());
+
+        trx.meta.as_mut().unwrap().compute_units_consumed = None;
+        assert_eq!(None, trx.compute_units_consumed());
+
+        trx.meta = None;
+        assert_eq!(0, trx.fee());
+        assert_eq!(None, trx.compute_units_consumed());
+    }
+
+    #[test]
+    fn it_tries_resolving_account_at_boundary() {
+        let trx = privileged_trx();
+
+        assert_eq!(Some(Address(&bytes("a0"))), trx.try_account_at(0));
+        assert_eq!(Some(Address(&bytes("a4"))), trx.try_account_at(4));
+        assert_eq!(Some(Address(&bytes("a5"))), trx.try_account_at(5));
+        assert_eq!(None, trx.try_account_at(6));
+        assert_eq!(None, trx.try_account_at(255));
+
+        let mut without_meta = privileged_trx();
+        without_meta.meta = None;
+        assert_eq!(Some(Address(&bytes("a3"))), without_meta.try_account_at(3));
+        assert_eq!(None, without_meta.try_account_at(4));
+
+        assert_eq!(None, pb::ConfirmedTransaction::default().try_account_at(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account index 6 out of bounds")]
+    fn it_panics_resolving_account_past_boundary() {
+        privileged_trx().account_at(6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account index 4 out of bounds")]
+    fn it_panics_resolving_loaded_account_without_meta() {
+        let mut trx = privileged_trx();
+        trx.meta = None;
+
+        trx.account_at(4);
+    }
+
+    #[test]
+    fn it_resolves_program_id_of_stored_instruction() {
+        let trx = privileged_trx();
+        let stored = pb::CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0],
+            data: vec![],
+        };
+        let inner = pb::InnerInstruction {
+            program_id_index: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(bytes("a3"), crate::resolve_program_id(&stored, &trx));
+        assert_eq!(bytes("a5"), crate::resolve_program_id(&inner, &trx));
+    }
+
+    #[test]
+    fn it_resolves_account_source() {
+        let trx = privileged_trx();
+
+        assert_eq!(Some(AccountSource::Static), trx.account_source(3));
+        assert_eq!(Some(AccountSource::LoadedWritable), trx.account_source(4));
+        assert_eq!(Some(AccountSource::LoadedReadonly), trx.account_source(5));
+        assert_eq!(None, trx.account_source(6));
+
+        assert_eq!(false, trx.is_loaded_address(3));
+        assert_eq!(true, trx.is_loaded_address(4));
+        assert_eq!(true, trx.is_loaded_address(5));
+        assert_eq!(false, trx.is_loaded_address(6));
+    }
+
+    #[test]
+    fn it_iterates_lookup_loaded_accounts() {
+        let trx = privileged_trx();
+
+        assert_eq!(
+            vec![(bytes("a4"), true), (bytes("a5"), false)],
+            trx.lookup_loaded_accounts()
+                .map(|(a, writable)| (a.0.clone(), writable))
+                .collect::<Vec<_>>()
+        );
+
+        let mut trx = privileged_trx();
+        trx.meta = None;
+        assert_eq!(0, trx.lookup_loaded_accounts().count());
+    }
+
+    #[test]
+    fn it_partitions_writable_and_readonly_accounts() {
+        let trx = privileged_trx();
+
+        assert_eq!(
+            vec![bytes("a0"), bytes("a2"), bytes("a4")],
+            trx.writable_accounts()
+                .map(|a| a.0.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![bytes("a1"), bytes("a3"), bytes("a5")],
+            trx.readonly_accounts()
+                .map(|a| a.0.clone())
+                .collect::<Vec<_>>()
+        );
+
+        let mut without_message = privileged_trx();
+        without_message.transaction.as_mut().unwrap().message = None;
+        assert_eq!(0, without_message.writable_accounts().count());
+        assert_eq!(0, without_message.readonly_accounts().count());
+        assert_eq!(
+            0,
+            pb::ConfirmedTransaction::default()
+                .writable_accounts()
+                .count()
+        );
+    }
+
+    #[test]
+    fn it_returns_write_locks_in_canonical_order() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1], vec![2], vec![3]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 3,
+                        num_readonly_signed_accounts: 1,
+                        num_readonly_unsigned_accounts: 2,
+                    }),
+                    account_keys: vec![
+                        bytes("a0"),
+                        bytes("a1"),
+                        bytes("a2"),
+                        bytes("a3"),
+                        bytes("a4"),
+                        bytes("a5"),
+                        bytes("a6"),
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                loaded_writable_addresses: vec![bytes("b0"), bytes("b1")],
+                loaded_readonly_addresses: vec![bytes("b2")],
+                ..Default::default()
+            }),
+        };
+
+        assert_eq!(
+            vec![
+                bytes("a0"),
+                bytes("a1"),
+                bytes("a3"),
+                bytes("a4"),
+                bytes("b0"),
+                bytes("b1")
+            ],
+            trx.write_locks()
+                .into_iter()
+                .map(|a| a.0.clone())
+                .collect::<Vec<_>>()
+        );
+        assert!(pb::ConfirmedTransaction::default().write_locks().is_empty());
+    }
+
+    #[test]
+    fn it_returns_writable_signers() {
+        let trx = privileged_trx();
+
+        assert_eq!(
+            vec![bytes("a0")],
+            trx.writable_signers()
+                .map(|a| a.0.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_collects_token_mints() {
+        let balance = |account_index: u32, mint: &[u8]| pb::TokenBalance {
+            account_index,
+            mint: base58::encode(mint),
+            ..Default::default()
+        };
+
+        let mut trx = privileged_trx();
+        let meta = trx.meta.as_mut().unwrap();
+        meta.pre_token_balances = vec![balance(2, &[1; 32]), balance(3, &[2; 32])];
+        meta.post_token_balances = vec![
+            balance(2, &[1; 32]),
+            balance(3, &[2; 32]),
+            balance(4, &[3; 32]),
+        ];
+
+        let mut mints = trx.token_mints().into_iter().collect::<Vec<_>>();
+        mints.sort();
+        assert_eq!(
+            vec![
+                AddressOwned(vec![1; 32]),
+                AddressOwned(vec![2; 32]),
+                AddressOwned(vec![3; 32])
+            ],
+            mints
+        );
+
+        trx.meta = None;
+        assert!(trx.token_mints().is_empty());
+    }
+
+    #[test]
+    fn it_returns_token_account_owner_changes() {
+        let balance = |account_index: u32, owner: &[u8]| pb::TokenBalance {
+            account_index,
+            owner: base58::encode(owner),
+            ..Default::default()
+        };
+
+        let (alice, bob) = ([1; 32], [2; 32]);
+
+        let mut trx = privileged_trx();
+        let meta = trx.meta.as_mut().unwrap();
+        meta.pre_token_balances = vec![
+            balance(2, &alice),
+            balance(3, &alice),
+            balance(4, &bob),
+            balance(5, &alice),
+        ];
+        meta.post_token_balances = vec![
+            balance(1, &bob),
+            balance(2, &alice),
+            balance(3, &bob),
+            balance(5, &[]),
+        ];
+        meta.post_token_balances[3].owner = "0OIl".to_string();
+
+        assert_eq!(
+            vec![(
+                AddressOwned(bytes("a3")),
+                AddressOwned(alice.to_vec()),
+                AddressOwned(bob.to_vec())
+            )],
+            trx.owner_changes()
+        );
+
+        trx.meta = None;
+        assert!(trx.owner_changes().is_empty());
+    }
+
+    #[test]
+    fn it_computes_token_deltas_by_owner() {
+        let balance =
+            |account_index: u32, owner: &[u8], mint: &[u8], amount: &str| pb::TokenBalance {
+                account_index,
+                mint: base58::encode(mint),
+                owner: base58::encode(owner),
+                ui_token_amount: Some(pb::UiTokenAmount {
+                    amount: amount.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+
+        let (alice, bob, mint) = ([1; 32], [2; 32], [9; 32]);
+
+        let mut trx = privileged_trx();
+        let meta = trx.meta.as_mut().unwrap();
+        meta.pre_token_balances = vec![
+            balance(2, &alice, &mint, "1000"),
+            balance(4, &alice, &[8; 32], "50"),
+        ];
+        meta.post_token_balances = vec![
+            balance(2, &alice, &mint, "750"),
+            balance(3, &bob, &mint, "250"),
+        ];
+
+        let deltas = trx.token_deltas_by_owner();
+        assert_eq!(3, deltas.len());
+        assert_eq!(
+            Some(&-250),
+            deltas.get(&(AddressOwned(alice.to_vec()), AddressOwned(mint.to_vec())))
+        );
+        assert_eq!(
+            Some(&250),
+            deltas.get(&(AddressOwned(bob.to_vec()), AddressOwned(mint.to_vec())))
+        );
+        assert_eq!(
+            Some(&-50),
+            deltas.get(&(AddressOwned(alice.to_vec()), AddressOwned(vec![8; 32])))
+        );
+
+        // Account 2 handed over to bob through SetAuthority, with 100 tokens transferred out
+        let meta = trx.meta.as_mut().unwrap();
+        meta.pre_token_balances = vec![balance(2, &alice, &mint, "1000")];
+        meta.post_token_balances = vec![balance(2, &bob, &mint, "900")];
+
+        let deltas = trx.token_deltas_by_owner();
+        assert_eq!(2, deltas.len());
+        assert_eq!(
+            Some(&-1000),
+            deltas.get(&(AddressOwned(alice.to_vec()), AddressOwned(mint.to_vec())))
+        );
+        assert_eq!(
+            Some(&900),
+            deltas.get(&(AddressOwned(bob.to_vec()), AddressOwned(mint.to_vec())))
+        );
+
+        trx.meta = None;
+        assert!(trx.token_deltas_by_owner().is_empty());
+    }
+
+    #[test]
+    fn it_returns_id_bytes() {
+        let signature = (0..64).collect::<Vec<u8>>();
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![signature.clone(), vec![7; 64]],
+                message: None,
+            }),
+            meta: None,
+        };
+
+        assert_eq!(
+            Some(signature.as_slice()),
+            trx.id_bytes().as_ref().map(|id| &id[..])
+        );
+        assert_eq!(bs58::encode(&signature).into_string(), trx.id());
+
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: None,
+            }),
+            meta: None,
+        };
+        assert_eq!(None, trx.id_bytes());
+        assert_eq!(None, pb::ConfirmedTransaction::default().id_bytes());
+    }
+
+    #[test]
+    fn it_validates_signature_count() {
+        let mut trx = privileged_trx();
+        assert_eq!(true, trx.is_signature_count_valid());
+
+        trx.transaction.as_mut().unwrap().signatures.pop();
+        assert_eq!(false, trx.is_signature_count_valid());
+
+        trx.transaction.as_mut().unwrap().signatures = vec![vec![1], vec![2], vec![3]];
+        assert_eq!(false, trx.is_signature_count_valid());
+
+        assert_eq!(
+            false,
+            pb::ConfirmedTransaction::default().is_signature_count_valid()
+        );
+    }
+
+    #[test]
+    fn it_computes_serialized_size() {
+        // A legacy SOL transfer transaction is known to be 215 bytes long
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1; 64]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![vec![1; 32], vec![2; 32], vec![0; 32]],
+                    recent_blockhash: vec![3; 32],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 2,
+                        accounts: vec![0, 1],
+                        data: vec![2, 0, 0, 0, 0x40, 0x42, 0x0f, 0, 0, 0, 0, 0],
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: None,
+        };
+        assert_eq!(215, trx.serialized_size());
+
+        // Same as a v0 message with a lookup table (1 writable, 2 readonly indexes) adds
+        // 1 byte of version prefix, 1 byte of lookups count and 32 + 2 + 3 bytes of lookup
+        let mut trx = trx;
+        let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+        message.versioned = true;
+        message.address_table_lookups = vec![pb::MessageAddressTableLookup {
+            account_key: vec![4; 32],
+            writable_indexes: vec![0],
+            readonly_indexes: vec![1, 2],
+        }];
+        assert_eq!(215 + 1 + 1 + 32 + 2 + 3, trx.serialized_size());
+
+        assert_eq!(0, pb::ConfirmedTransaction::default().serialized_size());
+    }
+
+    #[test]
+    fn it_returns_account_index_error_with_context() {
+        let trx = privileged_trx();
+
+        assert_eq!(Ok(Address(&bytes("a4"))), trx.account_at_result(4));
+        assert_eq!(
+            Err(AccountIndexError { index: 6, len: 6 }),
+            trx.account_at_result(6)
+        );
+        assert_eq!(
+            Err(AccountIndexError { index: 200, len: 6 }),
+            trx.account_at_result(200)
+        );
+
+        let mut without_meta = privileged_trx();
+        without_meta.meta = None;
+        assert_eq!(
+            Err(AccountIndexError { index: 4, len: 4 }),
+            without_meta.account_at_result(4)
+        );
+
+        assert_eq!(
+            Err(AccountIndexError { index: 0, len: 0 }),
+            pb::ConfirmedTransaction::default().account_at_result(0)
+        );
+    }
+
+    #[test]
+    fn it_computes_stable_fingerprint() {
+        let trx = |signatures: Vec<Vec<u8>>| pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            0x8368214f77995ee5,
+            trx(vec![(0..64).collect(), vec![1, 2, 3]]).fingerprint()
+        );
+        assert_eq!(0xd0aa6218672cf5ab, trx(vec![vec![1, 2, 3]]).fingerprint());
+        assert_eq!(0xcbf29ce484222325, trx(vec![]).fingerprint());
+        assert_eq!(
+            0xcbf29ce484222325,
+            pb::ConfirmedTransaction::default().fingerprint()
+        );
+    }
+
+    #[test]
+    fn it_computes_compact_u16_len() {
+        assert_eq!(1, super::compact_u16_len(0));
+        assert_eq!(1, super::compact_u16_len(0x7f));
+        assert_eq!(2, super::compact_u16_len(0x80));
+        assert_eq!(2, super::compact_u16_len(0x3fff));
+        assert_eq!(3, super::compact_u16_len(0x4000));
+    }
+
+    // Transaction with a0 (writable signer), a1 (readonly signer), a2 (writable),
+    // a3 (readonly), a4 (loaded writable) and a5 (loaded readonly).
+    fn privileged_trx() -> pb::ConfirmedTransaction {
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3], vec![4, 5, 6]],
+                message: Some(pb::Message {
+                    header: Some(pb::MessageHeader {
+                        num_required_signatures: 2,
+                        num_readonly_signed_accounts: 1,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![bytes("a0"), bytes("a1"), bytes("a2"), bytes("a3")],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta {
+                loaded_writable_addresses: vec![bytes("a4")],
+                loaded_readonly_addresses: vec![bytes("a5")],
+                ..Default::default()
+            }),
+        }
+    }
+
     fn bytes(s: &str) -> Vec<u8> {
         ::hex::decode(s).unwrap()
     }
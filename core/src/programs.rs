@@ -0,0 +1,101 @@
+//! Ids of well-known programs.
+
+use substreams_solana_macro::b58;
+
+use crate::{block_view::InstructionView, compute_budget::COMPUTE_BUDGET_PROGRAM_ID};
+
+/// The System program id, `11111111111111111111111111111111`.
+pub const SYSTEM_PROGRAM_ID: [u8; 32] = b58!("11111111111111111111111111111111");
+
+/// The Associated Token Account program id, `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`.
+pub const ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID: [u8; 32] =
+    b58!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// The Token program id, `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`.
+pub const TOKEN_PROGRAM_ID: [u8; 32] = b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// The Token-2022 program id, `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`.
+pub const TOKEN_2022_PROGRAM_ID: [u8; 32] = b58!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// The ids of the programs considered native by [InstructionView::is_native_program]: the
+/// runtime's builtin programs and the core SPL Token programs.
+///
+/// - System `11111111111111111111111111111111`
+/// - Vote `Vote111111111111111111111111111111111111111`
+/// - Stake `Stake11111111111111111111111111111111111111`
+/// - Config `Config1111111111111111111111111111111111111`
+/// - Compute Budget `ComputeBudget111111111111111111111111111111`
+/// - Address Lookup Table `AddressLookupTab1e1111111111111111111111111`
+/// - BPF Loader (deprecated) `BPFLoader1111111111111111111111111111111111`
+/// - BPF Loader `BPFLoader2111111111111111111111111111111111`
+/// - BPF Upgradeable Loader `BPFLoaderUpgradeab1e11111111111111111111111`
+/// - Loader v4 `LoaderV411111111111111111111111111111111111`
+/// - Ed25519 signature verification `Ed25519SigVerify111111111111111111111111111`
+/// - Secp256k1 signature verification `KeccakSecp256k11111111111111111111111111111`
+/// - Token `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`
+/// - Token-2022 `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`
+/// - Associated Token Account `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`
+pub const NATIVE_PROGRAM_IDS: [[u8; 32]; 15] = [
+    SYSTEM_PROGRAM_ID,
+    b58!("Vote111111111111111111111111111111111111111"),
+    b58!("Stake11111111111111111111111111111111111111"),
+    b58!("Config1111111111111111111111111111111111111"),
+    COMPUTE_BUDGET_PROGRAM_ID,
+    b58!("AddressLookupTab1e1111111111111111111111111"),
+    b58!("BPFLoader1111111111111111111111111111111111"),
+    b58!("BPFLoader2111111111111111111111111111111111"),
+    b58!("BPFLoaderUpgradeab1e11111111111111111111111"),
+    b58!("LoaderV411111111111111111111111111111111111"),
+    b58!("Ed25519SigVerify111111111111111111111111111"),
+    b58!("KeccakSecp256k11111111111111111111111111111"),
+    TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
+    ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+];
+
+impl InstructionView<'_> {
+    /// Returns `true` if the instruction's program is one of [NATIVE_PROGRAM_IDS], a coarse
+    /// way to separate infrastructure calls from application calls.
+    pub fn is_native_program(&self) -> bool {
+        self.program_is_one_of(&NATIVE_PROGRAM_IDS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SYSTEM_PROGRAM_ID;
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    #[test]
+    fn it_classifies_native_programs() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0xa0; 32], SYSTEM_PROGRAM_ID.to_vec(), vec![0xa2; 32]],
+                    instructions: vec![
+                        pb::CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0],
+                            data: vec![],
+                        },
+                        pb::CompiledInstruction {
+                            program_id_index: 2,
+                            accounts: vec![0],
+                            data: vec![],
+                        },
+                    ],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        };
+
+        let native = trx
+            .compiled_instructions()
+            .map(|view| view.is_native_program())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![true, false], native);
+    }
+}
@@ -0,0 +1,598 @@
+//! Decoders for the Solana Program Library (SPL) programs instructions, available with
+//! the `spl` feature.
+
+use substreams_solana_macro::b58;
+
+use crate::{
+    address::Address, base58, block_view::InstructionView, cursor::DataCursor,
+    pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+pub use crate::programs::{
+    ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+/// The native mint, `So11111111111111111111111111111111111111112`, the mint of wrapped SOL
+/// (wSOL) token accounts.
+pub const NATIVE_MINT: [u8; 32] = b58!("So11111111111111111111111111111111111111112");
+
+/// The Memo program id, `MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr`.
+pub const MEMO_PROGRAM_ID: [u8; 32] = b58!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// The legacy v1 Memo program id, `Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo`.
+pub const MEMO_V1_PROGRAM_ID: [u8; 32] = b58!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+
+const SYNC_NATIVE_DISCRIMINATOR: u8 = 17;
+const TRANSFER_FEE_EXTENSION_DISCRIMINATOR: u8 = 26;
+const TRANSFER_CHECKED_WITH_FEE_DISCRIMINATOR: u8 = 1;
+
+/// Returns `true` if `address` is a wrapped SOL (wSOL) token account in the transaction,
+/// e.g. if one of the transaction's pre or post token balances is for `address` with the
+/// [NATIVE_MINT] as mint.
+///
+/// Returns `false` if the transaction has no meta or no message.
+pub fn is_wsol_account(address: &[u8], trx: &ConfirmedTransaction) -> bool {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) => meta,
+        None => return false,
+    };
+
+    let native_mint = base58::encode(NATIVE_MINT);
+
+    meta.pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .filter(|balance| balance.mint == native_mint)
+        .any(|balance| {
+            u8::try_from(balance.account_index)
+                .ok()
+                .and_then(|index| trx.try_account_at(index))
+                .map_or(false, |account| account == address)
+        })
+}
+
+/// The SPL Token program an instruction or account belongs to. The Token-2022 program is a
+/// superset of the original Token program sharing its instruction layouts, so the helpers of
+/// this module accept both and report which one was used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenProgram {
+    /// The original Token program, [TOKEN_PROGRAM_ID].
+    Token,
+    /// The Token-2022 program, [TOKEN_2022_PROGRAM_ID].
+    Token2022,
+}
+
+impl TokenProgram {
+    /// Returns the token program with the given id, [None] if it's neither the Token nor
+    /// the Token-2022 program.
+    pub fn from_program_id(program_id: &[u8]) -> Option<Self> {
+        if program_id == TOKEN_PROGRAM_ID {
+            Some(TokenProgram::Token)
+        } else if program_id == TOKEN_2022_PROGRAM_ID {
+            Some(TokenProgram::Token2022)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the token program.
+    pub fn program_id(&self) -> [u8; 32] {
+        match self {
+            TokenProgram::Token => TOKEN_PROGRAM_ID,
+            TokenProgram::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+}
+
+/// The creation of an associated token account, see [InstructionView::ata_creation].
+#[derive(Debug)]
+pub struct AtaCreation<'a> {
+    /// The account paying for the creation of the associated token account.
+    pub funder: Address<'a>,
+    /// The associated token account created.
+    pub ata: Address<'a>,
+    /// The wallet owning the associated token account.
+    pub owner: Address<'a>,
+    /// The mint of the associated token account.
+    pub mint: Address<'a>,
+    /// `true` if created through `CreateIdempotent` which does not fail if the account
+    /// already exists.
+    pub idempotent: bool,
+    /// The token program owning the associated token account, [None] if the instruction
+    /// omits it or if it's not a known token program.
+    pub token_program: Option<TokenProgram>,
+}
+
+/// A Token-2022 `TransferCheckedWithFee` instruction of the transfer fee extension, see
+/// [InstructionView::token2022_transfer_checked_with_fee].
+#[derive(Debug)]
+pub struct TransferCheckedWithFee<'a> {
+    /// The token account debited.
+    pub source: Address<'a>,
+    /// The mint of the transferred tokens.
+    pub mint: Address<'a>,
+    /// The token account credited.
+    pub destination: Address<'a>,
+    /// The owner or delegate of the source account, a multisig when more signers follow it
+    /// in the instruction's accounts.
+    pub authority: Address<'a>,
+    /// The amount of tokens transferred, in base units, the fee included.
+    pub amount: u64,
+    /// The decimals of the mint.
+    pub decimals: u8,
+    /// The transfer fee withheld in the destination account, in base units.
+    pub fee: u64,
+}
+
+impl<'a> InstructionView<'a> {
+    /// Returns the token program invoked by the instruction, [None] if it invokes neither
+    /// the Token nor the Token-2022 program.
+    pub fn token_program(&self) -> Option<TokenProgram> {
+        TokenProgram::from_program_id(self.program_id().0)
+    }
+
+    /// Returns `true` if the instruction is a `SyncNative` instruction of the Token or
+    /// Token-2022 program, which syncs a wrapped SOL (wSOL) token account's amount with its
+    /// lamports, typically right after SOL has been transferred to it to wrap it.
+    pub fn syncs_native(&self) -> bool {
+        self.sync_native().is_some()
+    }
+
+    /// Returns the token program of the instruction if it's a `SyncNative` instruction, see
+    /// [Self::syncs_native], [None] otherwise.
+    pub fn sync_native(&self) -> Option<TokenProgram> {
+        let token_program = self.token_program()?;
+        if self.data().first() != Some(&SYNC_NATIVE_DISCRIMINATOR) {
+            return None;
+        }
+
+        Some(token_program)
+    }
+
+    /// Decodes the instruction as a Token-2022 `TransferCheckedWithFee` instruction, [None]
+    /// if the instruction is not one.
+    ///
+    /// The instruction's data is the transfer fee extension discriminator `26`, followed by
+    /// the `TransferCheckedWithFee` discriminator `1`, the amount as a little-endian `u64`,
+    /// the decimals as a `u8` and the fee as a little-endian `u64`. Its accounts are the
+    /// source, the mint, the destination and the authority, followed by the signers when
+    /// the authority is a multisig.
+    ///
+    /// The transfer fee extension only exists on Token-2022, the instruction is never
+    /// decoded for the original Token program.
+    pub fn token2022_transfer_checked_with_fee(&self) -> Option<TransferCheckedWithFee<'a>> {
+        if self.program_id() != TOKEN_2022_PROGRAM_ID {
+            return None;
+        }
+
+        let mut cursor = self.cursor();
+        if cursor.read_u8()? != TRANSFER_FEE_EXTENSION_DISCRIMINATOR
+            || cursor.read_u8()? != TRANSFER_CHECKED_WITH_FEE_DISCRIMINATOR
+        {
+            return None;
+        }
+
+        let decoded = self.decode_transfer_checked_with_fee(cursor);
+        if decoded.is_none() {
+            trace_decode_failure!(self, "Token-2022 TransferCheckedWithFee");
+        }
+
+        decoded
+    }
+
+    fn decode_transfer_checked_with_fee(
+        &self,
+        mut cursor: DataCursor,
+    ) -> Option<TransferCheckedWithFee<'a>> {
+        let amount = cursor.read_u64_le()?;
+        let decimals = cursor.read_u8()?;
+        let fee = cursor.read_u64_le()?;
+
+        let mut accounts = self.accounts_iter();
+        Some(TransferCheckedWithFee {
+            source: accounts.next()?,
+            mint: accounts.next()?,
+            destination: accounts.next()?,
+            authority: accounts.next()?,
+            amount,
+            decimals,
+            fee,
+        })
+    }
+
+    /// Decodes the instruction as a memo of the Memo program, v1 or v2, [None] if the
+    /// instruction is not one. The memo is the whole instruction's data, which the program
+    /// requires to be valid UTF-8.
+    pub fn memo(&self) -> Option<&str> {
+        let program_id = self.program_id();
+        if program_id != MEMO_PROGRAM_ID && program_id != MEMO_V1_PROGRAM_ID {
+            return None;
+        }
+
+        let decoded = std::str::from_utf8(self.data()).ok();
+        if decoded.is_none() {
+            trace_decode_failure!(self, "Memo");
+        }
+
+        decoded
+    }
+
+    /// Decodes the instruction as an associated token account creation, either `Create`
+    /// (empty data or `0` discriminator) or `CreateIdempotent` (`1` discriminator), of the
+    /// Associated Token Account program. Returns [None] if the instruction is not one of
+    /// those.
+    ///
+    /// Both Token and Token-2022 accounts are created through the same program, the token
+    /// program is read from the instruction's sixth account, see [AtaCreation::token_program].
+    pub fn ata_creation(&self) -> Option<AtaCreation<'a>> {
+        if self.program_id() != ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID {
+            return None;
+        }
+
+        let idempotent = match self.data().first() {
+            None | Some(0) => false,
+            Some(1) => true,
+            _ => return None,
+        };
+
+        let token_program = self
+            .accounts_iter()
+            .nth(5)
+            .and_then(|account| TokenProgram::from_program_id(account.0));
+
+        let mut accounts = self.accounts().into_iter();
+        match (
+            accounts.next(),
+            accounts.next(),
+            accounts.next(),
+            accounts.next(),
+        ) {
+            (Some(funder), Some(ata), Some(owner), Some(mint)) => Some(AtaCreation {
+                funder,
+                ata,
+                owner,
+                mint,
+                idempotent,
+                token_program,
+            }),
+            _ => {
+                trace_decode_failure!(self, "ATA creation");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_wsol_account, TokenProgram, ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, MEMO_PROGRAM_ID,
+        MEMO_V1_PROGRAM_ID, NATIVE_MINT, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    };
+    use crate::base58;
+    use crate::pb::sf::solana::r#type::v1 as pb;
+
+    fn single_instruction_trx(
+        program_id: &[u8],
+        accounts: Vec<u8>,
+        data: Vec<u8>,
+    ) -> pb::ConfirmedTransaction {
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![
+                        vec![0; 32],
+                        vec![1; 32],
+                        vec![2; 32],
+                        vec![3; 32],
+                        vec![4; 32],
+                        vec![5; 32],
+                        program_id.to_vec(),
+                    ],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 6,
+                        accounts,
+                        data,
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        }
+    }
+
+    #[test]
+    fn it_decodes_ata_creation() {
+        let ata_creation = |data: Vec<u8>| {
+            let trx = single_instruction_trx(
+                &ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+                vec![0, 1, 2, 3, 4, 5],
+                data,
+            );
+            let view = trx.compiled_instructions().next().unwrap();
+            view.ata_creation().map(|creation| {
+                (
+                    creation.funder.0[0],
+                    creation.ata.0[0],
+                    creation.owner.0[0],
+                    creation.mint.0[0],
+                    creation.idempotent,
+                )
+            })
+        };
+
+        assert_eq!(Some((0, 1, 2, 3, false)), ata_creation(vec![]));
+        assert_eq!(Some((0, 1, 2, 3, false)), ata_creation(vec![0]));
+        assert_eq!(Some((0, 1, 2, 3, true)), ata_creation(vec![1]));
+        assert_eq!(None, ata_creation(vec![2]));
+
+        let trx = single_instruction_trx(&[9; 32], vec![0, 1, 2, 3, 4, 5], vec![]);
+        let view = trx.compiled_instructions().next().unwrap();
+        assert!(view.ata_creation().is_none());
+
+        let trx = single_instruction_trx(&ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, vec![0, 1], vec![1]);
+        let view = trx.compiled_instructions().next().unwrap();
+        assert!(view.ata_creation().is_none());
+    }
+
+    #[test]
+    fn it_decodes_memo() {
+        let memo = |program_id: &[u8], data: Vec<u8>| {
+            let trx = single_instruction_trx(program_id, vec![], data);
+            let view = trx.compiled_instructions().next().unwrap();
+            view.memo().map(str::to_string)
+        };
+
+        assert_eq!(
+            Some("hello".to_string()),
+            memo(&MEMO_PROGRAM_ID, b"hello".to_vec())
+        );
+        assert_eq!(
+            Some("hello".to_string()),
+            memo(&MEMO_V1_PROGRAM_ID, b"hello".to_vec())
+        );
+        assert_eq!(Some("".to_string()), memo(&MEMO_PROGRAM_ID, vec![]));
+        assert_eq!(None, memo(&MEMO_PROGRAM_ID, vec![0xff, 0xfe]));
+        assert_eq!(None, memo(&TOKEN_PROGRAM_ID, b"hello".to_vec()));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn it_traces_decode_failures() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        /// Counts the events of the decode failure hook.
+        struct CountingSubscriber(Arc<AtomicUsize>);
+
+        impl Subscriber for CountingSubscriber {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                if event.metadata().fields().field("program_id").is_some() {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let subscriber = CountingSubscriber(count.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let valid = single_instruction_trx(&MEMO_PROGRAM_ID, vec![], b"hello".to_vec());
+            assert!(valid
+                .compiled_instructions()
+                .next()
+                .unwrap()
+                .memo()
+                .is_some());
+            assert_eq!(0, count.load(Ordering::SeqCst));
+
+            let invalid = single_instruction_trx(&MEMO_PROGRAM_ID, vec![], vec![0xff]);
+            assert!(invalid
+                .compiled_instructions()
+                .next()
+                .unwrap()
+                .memo()
+                .is_none());
+            assert_eq!(1, count.load(Ordering::SeqCst));
+        });
+    }
+
+    #[test]
+    fn it_detects_sync_native() {
+        let syncs_native = |program_id: &[u8], data: Vec<u8>| {
+            let trx = single_instruction_trx(program_id, vec![0], data);
+            let view = trx.compiled_instructions().next().unwrap();
+            view.syncs_native()
+        };
+
+        assert!(syncs_native(&TOKEN_PROGRAM_ID, vec![17]));
+        assert!(syncs_native(&TOKEN_2022_PROGRAM_ID, vec![17]));
+        assert!(!syncs_native(
+            &TOKEN_PROGRAM_ID,
+            vec![3, 1, 0, 0, 0, 0, 0, 0, 0]
+        ));
+        assert!(!syncs_native(&TOKEN_PROGRAM_ID, vec![]));
+        assert!(!syncs_native(&[9; 32], vec![17]));
+    }
+
+    #[test]
+    fn it_tags_token_program() {
+        let sync_native = |program_id: &[u8]| {
+            let trx = single_instruction_trx(program_id, vec![0], vec![17]);
+            let view = trx.compiled_instructions().next().unwrap();
+            (view.token_program(), view.sync_native())
+        };
+
+        assert_eq!(
+            (Some(TokenProgram::Token), Some(TokenProgram::Token)),
+            sync_native(&TOKEN_PROGRAM_ID)
+        );
+        assert_eq!(
+            (Some(TokenProgram::Token2022), Some(TokenProgram::Token2022)),
+            sync_native(&TOKEN_2022_PROGRAM_ID)
+        );
+        assert_eq!((None, None), sync_native(&[9; 32]));
+
+        assert_eq!(TOKEN_PROGRAM_ID, TokenProgram::Token.program_id());
+        assert_eq!(TOKEN_2022_PROGRAM_ID, TokenProgram::Token2022.program_id());
+
+        let ata_token_program = |token_program_id: &[u8]| {
+            let mut trx = single_instruction_trx(
+                &ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+                vec![0, 1, 2, 3, 4, 5],
+                vec![1],
+            );
+            let message = trx.transaction.as_mut().unwrap().message.as_mut().unwrap();
+            message.account_keys[5] = token_program_id.to_vec();
+
+            let view = trx.compiled_instructions().next().unwrap();
+            view.ata_creation().unwrap().token_program
+        };
+
+        assert_eq!(
+            Some(TokenProgram::Token),
+            ata_token_program(&TOKEN_PROGRAM_ID)
+        );
+        assert_eq!(
+            Some(TokenProgram::Token2022),
+            ata_token_program(&TOKEN_2022_PROGRAM_ID)
+        );
+        assert_eq!(None, ata_token_program(&[9; 32]));
+    }
+
+    #[test]
+    fn it_detects_wsol_account() {
+        let mut trx = single_instruction_trx(&TOKEN_PROGRAM_ID, vec![0], vec![17]);
+        let meta = trx.meta.as_mut().unwrap();
+        meta.pre_token_balances.push(pb::TokenBalance {
+            account_index: 1,
+            mint: base58::encode(NATIVE_MINT),
+            ..Default::default()
+        });
+        meta.post_token_balances.push(pb::TokenBalance {
+            account_index: 2,
+            mint: base58::encode([7; 32]),
+            ..Default::default()
+        });
+
+        assert!(is_wsol_account(&[1; 32], &trx));
+        assert!(!is_wsol_account(&[2; 32], &trx));
+        assert!(!is_wsol_account(&[3; 32], &trx));
+    }
+
+    #[test]
+    fn it_detects_no_wsol_account_without_message() {
+        let trx = pb::ConfirmedTransaction {
+            transaction: None,
+            meta: Some(pb::TransactionStatusMeta {
+                pre_token_balances: vec![pb::TokenBalance {
+                    account_index: 1,
+                    mint: base58::encode(NATIVE_MINT),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+        };
+
+        assert!(!is_wsol_account(&[1; 32], &trx));
+    }
+
+    #[test]
+    fn it_decodes_token2022_transfer_checked_with_fee() {
+        let data = |discriminators: [u8; 2], fee: &[u8]| {
+            let mut data = discriminators.to_vec();
+            data.extend_from_slice(&1_000_000u64.to_le_bytes());
+            data.push(6);
+            data.extend_from_slice(fee);
+            data
+        };
+        let decode = |program_id: &[u8], accounts: Vec<u8>, data: Vec<u8>| {
+            let trx = single_instruction_trx(program_id, accounts, data);
+            let view = trx.compiled_instructions().next().unwrap();
+            view.token2022_transfer_checked_with_fee().map(|transfer| {
+                (
+                    transfer.source.0[0],
+                    transfer.mint.0[0],
+                    transfer.destination.0[0],
+                    transfer.authority.0[0],
+                    transfer.amount,
+                    transfer.decimals,
+                    transfer.fee,
+                )
+            })
+        };
+
+        let fee = 2_500u64.to_le_bytes();
+        assert_eq!(
+            Some((0, 1, 2, 3, 1_000_000, 6, 2_500)),
+            decode(
+                &TOKEN_2022_PROGRAM_ID,
+                vec![0, 1, 2, 3],
+                data([26, 1], &fee)
+            )
+        );
+        // Multisig authority followed by its signers
+        assert_eq!(
+            Some((0, 1, 2, 3, 1_000_000, 6, 2_500)),
+            decode(
+                &TOKEN_2022_PROGRAM_ID,
+                vec![0, 1, 2, 3, 4, 5],
+                data([26, 1], &fee)
+            )
+        );
+
+        // Wrong program, extension instruction or truncated data and accounts
+        assert_eq!(
+            None,
+            decode(&TOKEN_PROGRAM_ID, vec![0, 1, 2, 3], data([26, 1], &fee))
+        );
+        assert_eq!(
+            None,
+            decode(
+                &TOKEN_2022_PROGRAM_ID,
+                vec![0, 1, 2, 3],
+                data([26, 0], &fee)
+            )
+        );
+        assert_eq!(
+            None,
+            decode(
+                &TOKEN_2022_PROGRAM_ID,
+                vec![0, 1, 2, 3],
+                data([12, 1], &fee)
+            )
+        );
+        assert_eq!(
+            None,
+            decode(
+                &TOKEN_2022_PROGRAM_ID,
+                vec![0, 1, 2, 3],
+                data([26, 1], &fee[..4])
+            )
+        );
+        assert_eq!(
+            None,
+            decode(&TOKEN_2022_PROGRAM_ID, vec![0, 1, 2], data([26, 1], &fee))
+        );
+    }
+}
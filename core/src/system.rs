@@ -0,0 +1,142 @@
+//! Decoders for the System program instructions, available with the `system` feature.
+
+use crate::{
+    address::{Address, Pubkey32},
+    block_view::InstructionView,
+    cursor::DataCursor,
+    programs::SYSTEM_PROGRAM_ID,
+};
+
+const CREATE_ACCOUNT_WITH_SEED_INDEX: u32 = 3;
+
+/// A System `CreateAccountWithSeed` instruction, see [InstructionView::create_account_with_seed].
+///
+/// The `base` and `owner` public keys are read from the instruction's data, not from its
+/// accounts, and are thus [Pubkey32] rather than [Address].
+#[derive(Debug)]
+pub struct CreateAccountWithSeed<'a> {
+    /// The account funding the creation.
+    pub from: Address<'a>,
+    /// The created account, derived from `base`, `seed` and `owner`.
+    pub to: Address<'a>,
+    /// The base public key used to derive the created account's address.
+    pub base: Pubkey32,
+    /// The seed used to derive the created account's address.
+    pub seed: String,
+    /// The lamports transferred to the created account.
+    pub lamports: u64,
+    /// The bytes of memory allocated for the created account.
+    pub space: u64,
+    /// The program owning the created account.
+    pub owner: Pubkey32,
+}
+
+impl<'a> InstructionView<'a> {
+    /// Decodes the instruction as a System `CreateAccountWithSeed` instruction, [None] if the
+    /// instruction is not one.
+    ///
+    /// The instruction's data is the bincode encoding of the instruction's index (`3` as a
+    /// little-endian `u32`) followed by the base public key, the seed (a little-endian `u64`
+    /// length followed by the UTF-8 bytes), the lamports, the space and the owner public key.
+    pub fn create_account_with_seed(&self) -> Option<CreateAccountWithSeed<'a>> {
+        if self.program_id() != SYSTEM_PROGRAM_ID {
+            return None;
+        }
+
+        let mut cursor = self.cursor();
+        if cursor.read_u32_le()? != CREATE_ACCOUNT_WITH_SEED_INDEX {
+            return None;
+        }
+
+        let decoded = self.decode_create_account_with_seed(cursor);
+        if decoded.is_none() {
+            trace_decode_failure!(self, "System CreateAccountWithSeed");
+        }
+
+        decoded
+    }
+
+    fn decode_create_account_with_seed(
+        &self,
+        mut cursor: DataCursor,
+    ) -> Option<CreateAccountWithSeed<'a>> {
+        let base = Pubkey32(*cursor.read_pubkey()?);
+        let seed_len = usize::try_from(cursor.read_u64_le()?).ok()?;
+        let seed = String::from_utf8(cursor.read_bytes(seed_len)?.to_vec()).ok()?;
+        let lamports = cursor.read_u64_le()?;
+        let space = cursor.read_u64_le()?;
+        let owner = Pubkey32(*cursor.read_pubkey()?);
+
+        let mut accounts = self.accounts().into_iter();
+        Some(CreateAccountWithSeed {
+            from: accounts.next()?,
+            to: accounts.next()?,
+            base,
+            seed,
+            lamports,
+            space,
+            owner,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{address::Pubkey32, pb::sf::solana::r#type::v1 as pb, programs::SYSTEM_PROGRAM_ID};
+
+    fn create_account_with_seed_data(seed: &str) -> Vec<u8> {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[7; 32]);
+        data.extend_from_slice(&(seed.len() as u64).to_le_bytes());
+        data.extend_from_slice(seed.as_bytes());
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        data.extend_from_slice(&165u64.to_le_bytes());
+        data.extend_from_slice(&[8; 32]);
+        data
+    }
+
+    fn system_trx(data: Vec<u8>) -> pb::ConfirmedTransaction {
+        pb::ConfirmedTransaction {
+            transaction: Some(pb::Transaction {
+                signatures: vec![vec![1, 2, 3]],
+                message: Some(pb::Message {
+                    account_keys: vec![vec![0; 32], vec![1; 32], SYSTEM_PROGRAM_ID.to_vec()],
+                    instructions: vec![pb::CompiledInstruction {
+                        program_id_index: 2,
+                        accounts: vec![0, 1],
+                        data,
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            meta: Some(pb::TransactionStatusMeta::default()),
+        }
+    }
+
+    #[test]
+    fn it_decodes_create_account_with_seed() {
+        let trx = system_trx(create_account_with_seed_data("vault"));
+        let view = trx.compiled_instructions().next().unwrap();
+        let instruction = view.create_account_with_seed().unwrap();
+
+        assert_eq!(vec![0; 32], instruction.from);
+        assert_eq!(vec![1; 32], instruction.to);
+        assert_eq!(Pubkey32([7; 32]), instruction.base);
+        assert_eq!("vault", instruction.seed);
+        assert_eq!(1_000_000, instruction.lamports);
+        assert_eq!(165, instruction.space);
+        assert_eq!(Pubkey32([8; 32]), instruction.owner);
+
+        // Transfer instruction
+        let trx = system_trx(vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let view = trx.compiled_instructions().next().unwrap();
+        assert!(view.create_account_with_seed().is_none());
+
+        // Truncated data
+        let mut data = create_account_with_seed_data("vault");
+        data.truncate(data.len() - 1);
+        let trx = system_trx(data);
+        let view = trx.compiled_instructions().next().unwrap();
+        assert!(view.create_account_with_seed().is_none());
+    }
+}
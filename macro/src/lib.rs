@@ -1,5 +1,6 @@
 //! This crate provides the `b58!` macro for converting base58 string literals
-//! to a byte array at compile time.
+//! to a byte array at compile time, and its `b58_array!` sibling checking the
+//! decoded length.
 //!
 //! # Examples
 //! ```
@@ -12,7 +13,6 @@
 //! ```
 extern crate proc_macro;
 use proc_macro::{Delimiter, Group, Literal, Punct, Spacing, TokenStream, TokenTree};
-use bs58;
 
 /// Strips any outer `Delimiter::None` groups from the input,
 /// returning a `TokenStream` consisting of the innermost
@@ -34,47 +34,146 @@ fn ignore_groups(mut input: TokenStream) -> TokenStream {
 }
 
 
+/// Decodes the base58 string literal of the macro's input, panicking (e.g. failing the
+/// compilation) if the input is not a valid base58 string literal.
+fn decode_literal(input: TokenStream) -> Vec<u8> {
+    match ignore_groups(input).into_iter().next() {
+        Some(tt) => decode_token(tt),
+        None => panic!("expected a string literal"),
+    }
+}
+
+/// Decodes the base58 string literal token, panicking if it's not a valid base58 string
+/// literal.
+fn decode_token(tt: TokenTree) -> Vec<u8> {
+    match tt {
+        TokenTree::Literal(literal) => {
+            let mut input = literal.to_string();
+
+            match input.as_bytes() {
+                [b'"', .., b'"'] => (),
+                _ => panic!("expected string literal, got `{}`", literal),
+            };
+
+            input.retain(|c| !r#"""#.contains(c));
+            match bs58::decode(input).into_vec() {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    panic!("failed to decode string literal `{}`", e)
+                }
+            }
+        }
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            decode_literal(group.stream())
+        }
+        unexpected => panic!("expected string literal, got `{}`", unexpected),
+    }
+}
+
+/// Parses the expected length token of `b58_array!`, an unsuffixed or `usize` suffixed
+/// integer literal.
+fn parse_length(tt: TokenTree) -> usize {
+    match tt {
+        TokenTree::Literal(literal) => {
+            let input = literal.to_string();
+            match input.trim_end_matches("usize").replace('_', "").parse() {
+                Ok(len) => len,
+                Err(_) => panic!("expected an integer length, got `{}`", literal),
+            }
+        }
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            match ignore_groups(group.stream()).into_iter().next() {
+                Some(tt) => parse_length(tt),
+                None => panic!("expected an integer length"),
+            }
+        }
+        unexpected => panic!("expected an integer length, got `{}`", unexpected),
+    }
+}
+
+/// Returns the bytes as an array literal, e.g. `[1u8, 2u8, ...]`.
+fn array_literal(bytes: Vec<u8>) -> TokenTree {
+    let mut tokens: Vec<TokenTree> = vec![];
+    let mut has_seen_first = false;
+    for v in bytes {
+        if has_seen_first {
+            tokens.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)))
+        } else {
+            has_seen_first = true;
+        }
+        tokens.push(TokenTree::Literal(Literal::u8_suffixed(v)))
+    }
+    let mut stream = TokenStream::new();
+    stream.extend(tokens);
+    TokenTree::Group(Group::new(Delimiter::Bracket, stream))
+}
+
 /// Macro for converting sequence of string literals containing base58 encoded data
 /// into an array of bytes.
 ///
 #[proc_macro]
 pub fn b58(input: TokenStream) -> TokenStream {
-    for tt in ignore_groups(input) {
-        match tt {
-            TokenTree::Literal(literal) => {
-                let mut input = literal.to_string();
-
-                match input.as_bytes() {
-                    [b'"', .., b'"'] => (),
-                    _ => panic!("expected string literal, got `{}`", literal),
-                };
-
-                input.retain(|c| !r#"""#.contains(c));
-                match  bs58::decode(input).into_vec() {
-                    Ok(bytes) => {
-                        let mut tokens: Vec<TokenTree> = vec![];
-                        let mut has_seen_first = false;
-                        for v in bytes {
-                            if has_seen_first {
-                                tokens.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)))
-                            } else {
-                                has_seen_first = true;
-                            }
-                            tokens.push(TokenTree::Literal(Literal::u8_suffixed(v)))
-                        }
-                        let mut foo = TokenStream::new();
-                        foo.extend(tokens.into_iter());
-                        let out = TokenTree::Group(Group::new(Delimiter::Bracket, foo));
-                        return TokenStream::from(out);
-
-                    },
-                    Err(e) => {
-                        panic!("failed to decode string literal `{}`", e)
-                    }
-                }
-            },
-            unexpected => panic!("expected string literal, got `{}`", unexpected),
-        };
+    TokenStream::from(array_literal(decode_literal(input)))
+}
+
+/// Macro for converting a string literal containing base58 encoded data into an array of
+/// exactly `N` bytes, e.g. `b58_array!("...", 32)`. The compilation fails with an
+/// `expected N bytes, got M` error if the decoded data is not exactly `N` bytes long,
+/// pointing at the macro invocation instead of at a type mismatch further away.
+#[proc_macro]
+pub fn b58_array(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+
+    let bytes = match tokens.next() {
+        Some(tt) => decode_token(tt),
+        None => panic!("expected a string literal"),
+    };
+
+    match tokens.next() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => (),
+        _ => panic!("expected `,` followed by the expected length"),
+    }
+
+    let len = match tokens.next() {
+        Some(tt) => parse_length(tt),
+        None => panic!("expected an integer length"),
+    };
+
+    match tokens.next() {
+        None => (),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' && tokens.next().is_none() => (),
+        Some(unexpected) => panic!("unexpected token `{}`", unexpected),
+    }
+
+    if bytes.len() != len {
+        panic!("expected {} bytes, got {}", len, bytes.len())
+    }
+
+    TokenStream::from(array_literal(bytes))
+}
+
+/// Macro for converting a string literal containing a base58 encoded public key into a
+/// `substreams_solana::Pubkey32`, usable in const context. The compilation fails if the
+/// decoded public key is not exactly 32 bytes long.
+///
+/// The expansion refers to `::substreams_solana::Pubkey32`, so the macro must be used from
+/// a crate depending on `substreams-solana`.
+#[proc_macro]
+pub fn pubkey(input: TokenStream) -> TokenStream {
+    let bytes = decode_literal(input);
+    if bytes.len() != 32 {
+        panic!("expected a 32 bytes public key, got {} bytes", bytes.len())
     }
-    panic!("expected a string literal")
+
+    let mut stream = TokenStream::new();
+    stream.extend(
+        "::substreams_solana::Pubkey32"
+            .parse::<TokenStream>()
+            .expect("valid path"),
+    );
+    stream.extend([TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        TokenStream::from(array_literal(bytes)),
+    ))]);
+    stream
 }
@@ -0,0 +1,10 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/const_array.rs");
+    t.pass("tests/ui/static_table.rs");
+    t.pass("tests/ui/b58_array.rs");
+    t.compile_fail("tests/ui/invalid_base58.rs");
+    t.compile_fail("tests/ui/b58_array_wrong_length.rs");
+    t.compile_fail("tests/ui/b58_array_invalid_base58.rs");
+}
@@ -0,0 +1,18 @@
+use substreams_solana_macro::{b58, b58_array};
+
+const TOKEN_PROGRAM_ID: [u8; 32] = b58_array!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", 32);
+const REG: [u8; 3] = b58_array!("reg", 3usize);
+
+macro_rules! program_id {
+    ($id:expr) => {
+        b58_array!($id, 32)
+    };
+}
+
+const SYSTEM_PROGRAM_ID: [u8; 32] = program_id!("11111111111111111111111111111111");
+
+fn main() {
+    assert_eq!(b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"), TOKEN_PROGRAM_ID);
+    assert_eq!([0x02, 0x8c, 0x6d], REG);
+    assert_eq!([0; 32], SYSTEM_PROGRAM_ID);
+}
@@ -0,0 +1,5 @@
+use substreams_solana_macro::b58_array;
+
+const INVALID: [u8; 3] = b58_array!("0OIl", 3);
+
+fn main() {}
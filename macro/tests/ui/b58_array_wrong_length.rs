@@ -0,0 +1,5 @@
+use substreams_solana_macro::b58_array;
+
+const TRUNCATED: [u8; 32] = b58_array!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5D", 32);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use substreams_solana_macro::b58;
+
+const TOKEN_PROGRAM_ID: [u8; 32] = b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const SYSTEM_PROGRAM_ID: [u8; 32] = b58!("11111111111111111111111111111111");
+
+fn main() {
+    assert_eq!(6, TOKEN_PROGRAM_ID[0]);
+    assert_eq!([0; 32], SYSTEM_PROGRAM_ID);
+}
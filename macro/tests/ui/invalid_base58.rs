@@ -0,0 +1,5 @@
+use substreams_solana_macro::b58;
+
+const INVALID: [u8; 3] = b58!("0OIl");
+
+fn main() {}
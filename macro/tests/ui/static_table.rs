@@ -0,0 +1,19 @@
+use substreams_solana_macro::b58;
+
+const TOKEN_PROGRAM_ID: [u8; 32] = b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+static IDS: &[[u8; 32]] = &[
+    b58!("11111111111111111111111111111111"),
+    TOKEN_PROGRAM_ID,
+    b58!("ComputeBudget111111111111111111111111111111"),
+    b58!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+];
+
+const MIXED: &[&[u8]] = &[&b58!("reg"), &b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")];
+
+fn main() {
+    assert_eq!(4, IDS.len());
+    assert_eq!(TOKEN_PROGRAM_ID, IDS[1]);
+    assert_eq!(3, MIXED[0].len());
+    assert_eq!(32, MIXED[1].len());
+}
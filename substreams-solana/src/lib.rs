@@ -34,5 +34,22 @@
 //!   element is a view over the instruction, has resolved accounts and provides access to the transaction, compiled
 //!   instruction  of the transaction.  It make it much easier to walk the whole instruction tree of a
 //!   transaction. Refer to the method documentation for more information about it.
-pub use substreams_solana_core::{address::Address, base58, block_view, pb, Instruction};
-pub use substreams_solana_macro::b58;
+#[cfg(feature = "anchor")]
+pub use substreams_solana_core::anchor;
+#[cfg(feature = "spl")]
+pub use substreams_solana_core::spl;
+#[cfg(feature = "system")]
+pub use substreams_solana_core::system;
+pub use substreams_solana_core::{
+    address::{Address, AddressBuf, AddressOwned, Allowlist, Pubkey32},
+    balance::StateChange,
+    base58, block_view, compute_budget,
+    cursor::DataCursor,
+    decode::DecodableInstruction,
+    discriminator::Discriminator,
+    error::{AccountIndexError, TransactionErrorKind, TransactionValidationError},
+    pb, programs, resolve_program_id,
+    resolved::ResolvedInstruction,
+    Instruction, TransactionVersion,
+};
+pub use substreams_solana_macro::{b58, b58_array, pubkey};
@@ -0,0 +1,19 @@
+use substreams_solana::{b58, pubkey, Address, Pubkey32};
+
+const TOKEN_PROGRAM_ID: Pubkey32 = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+#[test]
+fn it_expands_pubkey_in_const_context() {
+    assert_eq!(
+        b58!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        TOKEN_PROGRAM_ID.to_bytes()
+    );
+    assert_eq!(
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        TOKEN_PROGRAM_ID.to_string()
+    );
+
+    let bytes = TOKEN_PROGRAM_ID.to_bytes().to_vec();
+    assert_eq!(TOKEN_PROGRAM_ID, Address(&bytes));
+    assert_eq!(Address(&bytes), TOKEN_PROGRAM_ID);
+}